@@ -0,0 +1,334 @@
+#![allow(clippy::needless_return)]
+
+/*
+    Builds the actor list from the files in src/actors/, so adding an actor only needs its file.
+    Each struct implementing GameObj gets an Actor value named after it with an "A" in front,
+    holding the arguments of the new() function in its own impl block, and a match case in spawn_actor().
+    The result is written to OUT_DIR/actors.rs and included by src/actor.rs.
+*/
+
+use proc_macro2::{Delimiter, Ident, Span, TokenStream, TokenTree};
+use quote::{format_ident, quote};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::str::FromStr;
+
+const ACTORS_DIR: &str = "src/actors";
+
+struct ActorFile {
+    module: String,
+    path: String,
+    actors: Vec<ActorDef>
+}
+
+struct ActorDef {
+    name: String,
+    docs: Vec<TokenStream>, // The doc comments on new(), copied onto the Actor value.
+    params: Vec<(Ident, TokenStream)> // The name and type of each argument to new(), with types written as paths from the actor module.
+}
+
+struct NewFn {
+    docs: Vec<TokenStream>,
+    params: TokenStream
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed={}", ACTORS_DIR);
+
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR is not set");
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR is not set");
+
+    let mut files: Vec<_> = std::fs::read_dir(Path::new(&manifest_dir).join(ACTORS_DIR))
+        .expect("Unable to read the actors folder")
+        .map(|entry| entry.expect("Unable to read the actors folder").path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "rs"))
+        .collect();
+    files.sort(); // Keeps the generated file the same between builds.
+
+    let actor_files: Vec<ActorFile> = files.iter().map(|path| read_actor_file(path)).collect();
+    let output = generate(&actor_files);
+    std::fs::write(Path::new(&out_dir).join("actors.rs"), output.to_string()).expect("Unable to write the actor list");
+}
+
+fn read_actor_file(path: &Path) -> ActorFile {
+    let module = path.file_stem().unwrap().to_string_lossy().into_owned();
+    let source = std::fs::read_to_string(path).unwrap_or_else(|err| panic!("Unable to read {}: {}", path.display(), err));
+    let tokens: Vec<TokenTree> = TokenStream::from_str(&source)
+        .unwrap_or_else(|err| panic!("Unable to parse {}: {:?}", path.display(), err))
+        .into_iter()
+        .collect();
+
+    // Walks the top level of the file, noting imports, the types it defines and its impl blocks.
+    let mut imports: HashMap<String, Vec<String>> = HashMap::new();
+    let mut local_types: HashSet<String> = HashSet::new();
+    let mut game_objs: Vec<String> = Vec::new();
+    let mut impl_bodies: HashMap<String, Vec<TokenStream>> = HashMap::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        match &tokens[i] {
+            TokenTree::Ident(ident) if ident == "use" => {
+                let end = find_punct(&tokens, i, ';');
+                add_use_tree(&tokens[i + 1..end], &[], &mut imports);
+                i = end;
+            },
+            TokenTree::Ident(ident) if ident == "enum" || ident == "struct" || ident == "type" || ident == "trait" => {
+                if let Some(TokenTree::Ident(name)) = tokens.get(i + 1) {
+                    local_types.insert(name.to_string());
+                }
+            },
+            TokenTree::Ident(ident) if ident == "impl" => {
+                // Reads everything up to the body, so generics and where clauses are skipped over.
+                let Some(body_index) = tokens[i..].iter().position(|token| matches!(token, TokenTree::Group(group) if group.delimiter() == Delimiter::Brace)).map(|offset| i + offset) else {
+                    break;
+                };
+                let header = skip_generics(&tokens[i + 1..body_index]);
+                let for_index = header.iter().position(|token| matches!(token, TokenTree::Ident(ident) if ident == "for"));
+                match for_index {
+                    Some(for_index) => {
+                        if get_type_name(&header[..for_index]).is_some_and(|name| name == "GameObj") && let Some(name) = get_type_name(&header[for_index + 1..]) {
+                            game_objs.push(name);
+                        }
+                    },
+                    None => {
+                        if let (Some(name), TokenTree::Group(body)) = (get_type_name(header), &tokens[body_index]) {
+                            impl_bodies.entry(name).or_default().push(body.stream());
+                        }
+                    },
+                }
+                i = body_index;
+            },
+            _ => {},
+        }
+        i += 1;
+    }
+
+    let actors = game_objs.into_iter().map(|name| {
+        let new_fn = impl_bodies.get(&name)
+            .and_then(|bodies| bodies.iter().find_map(find_new_fn))
+            .unwrap_or_else(|| panic!("{} in {} implements GameObj but has no new() function", name, path.display()));
+        let params = split_params(new_fn.params, path, &name).into_iter().map(|(param, ty)| {
+            return (param, qualify_type(ty, &module, &local_types, &imports));
+        }).collect();
+        return ActorDef { name, docs: new_fn.docs, params };
+    }).collect();
+
+    return ActorFile { module, path: path.to_string_lossy().into_owned(), actors };
+}
+
+fn find_punct(tokens: &[TokenTree], start: usize, ch: char) -> usize {
+    return tokens[start..].iter()
+        .position(|token| matches!(token, TokenTree::Punct(punct) if punct.as_char() == ch))
+        .map(|offset| start + offset)
+        .unwrap_or(tokens.len());
+}
+
+fn add_use_tree(tokens: &[TokenTree], prefix: &[String], imports: &mut HashMap<String, Vec<String>>) {
+    // Records the full path of every name a use declaration brings in, following nested {} groups.
+    let mut path = prefix.to_vec();
+    let mut i = 0;
+    while i < tokens.len() {
+        match &tokens[i] {
+            TokenTree::Ident(ident) if ident == "as" => {
+                if let Some(TokenTree::Ident(alias)) = tokens.get(i + 1) && alias != "_" {
+                    imports.insert(alias.to_string(), path.clone());
+                }
+                return;
+            },
+            TokenTree::Ident(ident) if ident == "self" && !path.is_empty() => {
+                imports.insert(path.last().unwrap().clone(), path.clone());
+                return;
+            },
+            TokenTree::Ident(ident) => path.push(ident.to_string()),
+            TokenTree::Group(group) if group.delimiter() == Delimiter::Brace => {
+                let inner: Vec<TokenTree> = group.stream().into_iter().collect();
+                for branch in inner.split(|token| matches!(token, TokenTree::Punct(punct) if punct.as_char() == ',')) {
+                    add_use_tree(branch, &path, imports);
+                }
+                return;
+            },
+            _ => {},
+        }
+        i += 1;
+    }
+    if let Some(name) = path.last() && name != "*" {
+        imports.insert(name.clone(), path.clone());
+    }
+}
+
+fn skip_generics(tokens: &[TokenTree]) -> &[TokenTree] {
+    // Skips the generic parameters straight after "impl", if there are any.
+    if !matches!(tokens.first(), Some(TokenTree::Punct(punct)) if punct.as_char() == '<') {
+        return tokens;
+    }
+    let mut depth = 0;
+    for (index, token) in tokens.iter().enumerate() {
+        if let TokenTree::Punct(punct) = token {
+            match punct.as_char() {
+                '<' => depth += 1,
+                '>' => depth -= 1,
+                _ => {},
+            }
+            if depth == 0 {
+                return &tokens[index + 1..];
+            }
+        }
+    }
+    return &[];
+}
+
+fn get_type_name(tokens: &[TokenTree]) -> Option<String> {
+    // The last name in a path outside of any generics, stopping at a where clause. (example: "Name" from "crate::Name<'a> where")
+    let mut depth = 0;
+    let mut name = None;
+    for token in tokens {
+        match token {
+            TokenTree::Ident(ident) if ident == "where" => break,
+            TokenTree::Ident(ident) if depth == 0 => name = Some(ident.to_string()),
+            TokenTree::Punct(punct) if punct.as_char() == '<' => depth += 1,
+            TokenTree::Punct(punct) if punct.as_char() == '>' => depth -= 1,
+            _ => {},
+        }
+    }
+    return name;
+}
+
+fn find_new_fn(body: &TokenStream) -> Option<NewFn> {
+    let tokens: Vec<TokenTree> = body.clone().into_iter().collect();
+    let (fn_index, params) = tokens.windows(3).enumerate().find_map(|(index, window)| match window {
+        [TokenTree::Ident(fn_kw), TokenTree::Ident(name), TokenTree::Group(params)] if fn_kw == "fn" && name == "new" => Some((index, params.stream())),
+        _ => None,
+    })?;
+    // Walks back over the visibility to the attributes before it, keeping the doc comments.
+    let mut index = fn_index;
+    while index > 0 && match &tokens[index - 1] {
+        TokenTree::Ident(ident) => ident == "pub" || ident == "const",
+        TokenTree::Group(group) => group.delimiter() == Delimiter::Parenthesis,
+        _ => false,
+    } {
+        index -= 1;
+    }
+    let mut docs: Vec<TokenStream> = Vec::new();
+    while index > 1 && let (TokenTree::Punct(pound), TokenTree::Group(attr)) = (&tokens[index - 2], &tokens[index - 1]) && pound.as_char() == '#' && attr.delimiter() == Delimiter::Bracket {
+        if matches!(attr.stream().into_iter().next(), Some(TokenTree::Ident(ident)) if ident == "doc") {
+            docs.push(tokens[index - 2..index].iter().cloned().collect());
+        }
+        index -= 2;
+    }
+    docs.reverse();
+    return Some(NewFn { docs, params });
+}
+
+fn split_params(params: TokenStream, path: &Path, actor: &str) -> Vec<(Ident, TokenStream)> {
+    // Splits on commas outside of generics, then each argument on its first colon.
+    let mut split: Vec<Vec<TokenTree>> = vec![Vec::new()];
+    let mut depth = 0;
+    for token in params {
+        if let TokenTree::Punct(punct) = &token {
+            match punct.as_char() {
+                '<' => depth += 1,
+                '>' => depth -= 1,
+                ',' if depth == 0 => {
+                    split.push(Vec::new());
+                    continue;
+                },
+                _ => {},
+            }
+        }
+        split.last_mut().unwrap().push(token);
+    }
+    return split.into_iter().filter(|param| !param.is_empty()).map(|param| {
+        // A leading "mut" only matters inside new(), so it is left out of the match case.
+        let colon = find_punct(&param, 0, ':');
+        let pattern: &[TokenTree] = match param.first() {
+            Some(TokenTree::Ident(ident)) if ident == "mut" => &param[1..colon],
+            _ => &param[..colon],
+        };
+        let name = match pattern {
+            [TokenTree::Ident(name)] if name != "_" => Ident::new(&name.to_string(), Span::call_site()),
+            _ => {
+                let argument: TokenStream = param.iter().cloned().collect();
+                panic!("{}: the argument \"{}\" of {}::new() must be a plain name, so it can be used as part of the Actor value", path.display(), argument, actor);
+            },
+        };
+        return (name, param[colon + 1..].iter().cloned().collect());
+    }).collect();
+}
+
+fn qualify_type(ty: TokenStream, module: &str, local_types: &HashSet<String>, imports: &HashMap<String, Vec<String>>) -> TokenStream {
+    // Writes the first name of each path in the type out in full, so it can be used from outside the actors file.
+    let mut output = TokenStream::new();
+    let mut after_path_sep = false;
+    for token in ty {
+        match &token {
+            TokenTree::Ident(ident) if !after_path_sep => {
+                let name = ident.to_string();
+                let path: Option<Vec<String>> = if local_types.contains(&name) {
+                    Some(vec![module.to_string(), name])
+                } else {
+                    imports.get(&name).map(|path| {
+                        match path.first().map(|first| first.as_str()) {
+                            Some("self") => return [vec![module.to_string()], path[1..].to_vec()].concat(),
+                            Some("super") => return path[1..].to_vec(),
+                            _ => return path.clone(),
+                        }
+                    })
+                };
+                match path {
+                    Some(path) => output.extend(TokenStream::from_str(&path.join("::")).unwrap()),
+                    None => output.extend([token.clone()]),
+                }
+            },
+            TokenTree::Group(group) => {
+                let inner = proc_macro2::Group::new(group.delimiter(), qualify_type(group.stream(), module, local_types, imports));
+                output.extend([TokenTree::Group(inner)]);
+            },
+            _ => output.extend([token.clone()]),
+        }
+        after_path_sep = matches!(&token, TokenTree::Punct(punct) if punct.as_char() == ':');
+    }
+    return output;
+}
+
+fn generate(files: &[ActorFile]) -> TokenStream {
+    let mods = files.iter().map(|file| {
+        let module = format_ident!("{}", file.module);
+        let path = &file.path;
+        return quote! { #[path = #path] pub(crate) mod #module; };
+    });
+
+    let actors: Vec<(&ActorFile, &ActorDef)> = files.iter()
+        .flat_map(|file| file.actors.iter().map(move |actor| (file, actor)))
+        .collect();
+
+    let variants = actors.iter().map(|(_, actor)| {
+        let variant = format_ident!("A{}", actor.name);
+        let docs = &actor.docs;
+        let types = actor.params.iter().map(|(_, ty)| ty);
+        return quote! { #(#docs)* #variant(#(#types),*) };
+    });
+
+    let arms = actors.iter().map(|(file, actor)| {
+        let variant = format_ident!("A{}", actor.name);
+        let module = format_ident!("{}", file.module);
+        let name = format_ident!("{}", actor.name);
+        let params: Vec<&Ident> = actor.params.iter().map(|(param, _)| param).collect();
+        return quote! { Actor::#variant(#(#params),*) => return Box::new(#module::#name::new(#(#params),*)), };
+    });
+
+    return quote! {
+        #(#mods)*
+
+        #[derive(Clone, Copy)]
+        #[allow(clippy::enum_variant_names)] // Every value starts with "A" to keep it apart from the struct it spawns.
+        pub(crate) enum Actor {
+            #(#variants),*
+        }
+
+        pub fn spawn_actor(actor_type: Actor) -> Box<dyn GameObj> {
+            match actor_type {
+                #(#arms)*
+            }
+        }
+    };
+}
@@ -1,24 +1,10 @@
 /*
-    Contains a list of actors, each time we make a new one add it here.
+    Contains the list of actors, built from the files in src/actors/ by build.rs.
+    Each struct there implementing GameObj gets an Actor value named after it with an "A" in front,
+    holding the arguments of its new() function. (example: Enemy::new(starting_pos, behaviour) becomes AEnemy(Vector2D<i32>, Behaviour))
 */
 
-use agb::fixnum::Vector2D;
 use alloc::boxed::Box;
 use crate::game_obj::GameObj;
 
-#[path = "actors/player.rs"] pub(crate) mod player;
-#[path = "actors/wall.rs"] pub(crate) mod wall;
-//#[path = "actors/tilesheet.rs"] pub(crate) mod tilesheet;
-
-#[derive(Clone, Copy)]
-pub(crate) enum Actor {
-    APlayer(Vector2D<i32>),
-    AWall(Vector2D<i32>)
-}
-
-pub fn spawn_actor(actor_type: Actor) -> Box<dyn GameObj> {
-    match actor_type {
-        Actor::APlayer(pos) => return Box::new(player::Player::new(pos)),
-        Actor::AWall(pos) => return Box::new(wall::Wall::new(pos)),
-    }
-}
\ No newline at end of file
+include!(concat!(env!("OUT_DIR"), "/actors.rs"));
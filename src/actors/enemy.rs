@@ -0,0 +1,315 @@
+use agb::display::GraphicsFrame;
+use agb::fixnum::{Rect, Vector2D};
+use agb::include_aseprite;
+use alloc::{boxed::Box, vec::Vec};
+use crate::animation::{AnimMode, Animation, AnimationPlayer};
+use crate::collision::{BoxType, HitBox, HitLayer};
+use crate::game_sprite::GameSprite;
+use crate::game_obj::{Facing, GameObj, ResponseType};
+use crate::actor::projectile::{Owner, ProjectileRequest};
+use crate::pathfinding::PathState;
+use crate::{actor, global_data};
+
+include_aseprite!(
+    mod sprites,
+    "gfx/new_img.aseprite"
+);
+
+// Uses the placeholder player sprite until enemy frames are drawn.
+const IDLE_ANIM: Animation = Animation::new(&sprites::TEST_PLAYER, &[16], AnimMode::LOOP);
+
+const MAX_HEALTH: u8 = 3;
+const MOVE_DELAY: u16 = 2; // Frames between each pixel of movement.
+const SHOT_SPEED: i32 = 2;
+const HURT_FRAMES: u16 = 30;
+const KNOCKBACK_FRAMES: u16 = 6;
+const KNOCKBACK_SPEED: i32 = 2;
+const CENTER: Vector2D<i32> = Vector2D { x: 8, y: 8 };
+const REPATH_FRAMES: u16 = 60; // How often a hunting enemy looks for a new path to the player.
+const HITBOXES: [HitBox; 1] = [HitBox::new("body", BoxType::HURTBOX, HitLayer::ENEMY, Vector2D { x: 0, y: 0 }, Vector2D { x: 16, y: 16 })];
+
+#[derive(Clone, Copy)]
+pub(crate) enum Behaviour {
+    IDLE,
+    PATROL(Vector2D<i32>), // Walks back and forth between where it spawned and that point plus this offset.
+    CHASE(i32), // Walks towards the player while they are within this range.
+    FLEE(i32), // Walks away from the player while they are within this range.
+    SHOOT(i32, u16), // Faces the player while they are within this range, firing every given number of frames.
+    SNIPE(i32, u16), // Like SHOOT, but the shot hits instantly along a straight line.
+    HUNT(i32) // Finds a way around walls to the player while they are within this range.
+}
+
+pub(crate) struct Enemy {
+    object: GameSprite,
+    anim: AnimationPlayer,
+    col: Rect<i32>,
+    prev_pos: Vector2D<i32>,
+    origin: Vector2D<i32>,
+    behaviour: Behaviour,
+    facing: Facing,
+    patrol_forward: bool,
+    timer: u16,
+    health: u8,
+    hurt_timer: u16,
+    knockback: Vector2D<i32>,
+    path_request: Option<u16>,
+    path: Vec<Vector2D<i32>>, // The rest of the path in world space, with the next cell last.
+    path_age: u16,
+    free_ready: bool
+}
+
+impl Enemy {
+    pub fn new(starting_pos: Vector2D<i32>, behaviour: Behaviour) -> Enemy {
+        Enemy {
+            object: GameSprite::new(sprites::TEST_PLAYER.sprite(0)),
+            anim: AnimationPlayer::new(IDLE_ANIM),
+            col: Rect { position: starting_pos, size: Vector2D { x: 16, y: 16 } },
+            prev_pos: starting_pos,
+            origin: starting_pos,
+            behaviour,
+            facing: Facing::DOWN,
+            patrol_forward: true,
+            timer: 0,
+            health: MAX_HEALTH,
+            hurt_timer: 0,
+            knockback: Vector2D { x: 0, y: 0 },
+            path_request: None,
+            path: Vec::new(),
+            path_age: 0,
+            free_ready: false
+        }
+    }
+
+    fn run_behaviour(&mut self, globals: &mut global_data::GlobalData) {
+        let player_pos = globals.get_player_pos();
+        match self.behaviour {
+            Behaviour::IDLE => {},
+            Behaviour::PATROL(offset) => {
+                let target = if self.patrol_forward { self.origin + offset } else { self.origin };
+                if self.col.position == target {
+                    self.patrol_forward = !self.patrol_forward;
+                } else {
+                    self.step(target - self.col.position);
+                }
+            },
+            Behaviour::CHASE(range) => {
+                if let Some(pos) = player_pos && self.in_range(pos, range) && self.can_see(globals, pos) {
+                    self.step(pos - self.col.position);
+                }
+            },
+            Behaviour::FLEE(range) => {
+                if let Some(pos) = player_pos && self.in_range(pos, range) {
+                    self.step(self.col.position - pos);
+                }
+            },
+            Behaviour::SHOOT(range, cooldown) => {
+                if let Some(pos) = player_pos && self.in_range(pos, range) && self.can_see(globals, pos) {
+                    self.facing = Facing::from_vector(pos - self.col.position);
+                    if self.timer.is_multiple_of(cooldown.max(1)) {
+                        self.shoot(globals);
+                    }
+                }
+            },
+            Behaviour::SNIPE(range, cooldown) => {
+                if let Some(pos) = player_pos && self.in_range(pos, range) {
+                    self.snipe(globals, pos, cooldown);
+                }
+            },
+            Behaviour::HUNT(range) => {
+                if let Some(pos) = player_pos && self.in_range(pos, range) {
+                    self.hunt(globals, pos);
+                }
+            },
+        }
+    }
+
+    fn step(&mut self, direction: Vector2D<i32>) {
+        // Moves one pixel along each axis of the direction, every MOVE_DELAY frames.
+        if direction == (Vector2D { x: 0, y: 0 }) || !self.timer.is_multiple_of(MOVE_DELAY) {
+            return;
+        }
+        self.facing = Facing::from_vector(direction);
+        self.col.position += Vector2D { x: direction.x.signum(), y: direction.y.signum() };
+    }
+
+    fn in_range(&self, pos: Vector2D<i32>, range: i32) -> bool {
+        let distance = pos - self.col.position;
+        return distance.x * distance.x + distance.y * distance.y <= range * range;
+    }
+
+    fn can_see(&self, globals: &global_data::GlobalData, pos: Vector2D<i32>) -> bool {
+        // Looks from center to center, so walls between the enemy and the player block it.
+        return globals.has_line_of_sight(self.col.position + CENTER, pos + CENTER);
+    }
+
+    fn snipe(&mut self, globals: &mut global_data::GlobalData, pos: Vector2D<i32>, cooldown: u16) {
+        // A single cast both checks the player can be seen and finds where the shot lands.
+        let Some(hit) = globals.cast_segment(self.col.position + CENTER, pos + CENTER, &[ResponseType::WALL, ResponseType::PLAYER]) else {
+            return;
+        };
+        if hit.response != ResponseType::PLAYER {
+            return;
+        }
+        self.facing = Facing::from_vector(pos - self.col.position);
+        if self.timer.is_multiple_of(cooldown.max(1)) {
+            globals.spawn_queue(actor::Actor::AProjectile(ProjectileRequest::hitscan(hit.point, Owner::ENEMY)));
+        }
+    }
+
+    fn hunt(&mut self, globals: &mut global_data::GlobalData, pos: Vector2D<i32>) {
+        // New paths are only asked for while standing on a cell of the last one, then it waits there until the search is done.
+        // This way every path starts from the cell the enemy is lined up with.
+        self.path_age = self.path_age.saturating_add(1);
+        if let Some(id) = self.path_request {
+            match globals.take_path(id) {
+                PathState::SEARCHING => return,
+                PathState::FOUND(path) => {
+                    self.path = path;
+                    self.path.reverse();
+                    self.path_age = 0;
+                },
+                PathState::FAILED => {},
+            }
+            self.path_request = None;
+        }
+        let Some(next) = self.path.last() else {
+            self.path_request = globals.find_path(self.col.position + CENTER, pos + CENTER);
+            if self.path_request.is_none() {
+                // Walks straight at the player like CHASE when no search can start, such as outside the pathfinding grid.
+                self.step(pos - self.col.position);
+            }
+            return;
+        };
+        let target = globals.to_screen_pos(*next);
+        if target != self.col.position {
+            self.step(target - self.col.position);
+            return;
+        }
+        self.path.pop();
+        if self.path_age >= REPATH_FRAMES {
+            self.path.clear();
+        }
+    }
+
+    fn shoot(&mut self, globals: &mut global_data::GlobalData) {
+        // Fires from the center of the enemy in the direction it is facing.
+        let spawn_pos = self.col.position + Vector2D { x: 4, y: 4 };
+        let velocity = self.facing.get_vector() * SHOT_SPEED;
+        globals.spawn_queue(actor::Actor::AProjectile(ProjectileRequest::from_owner(spawn_pos, velocity, Owner::ENEMY)));
+    }
+
+    fn take_hit(&mut self, from: Option<Vector2D<i32>>) {
+        // Ignores hits while still hurt from the last one, so a single attack only counts once.
+        if self.hurt_timer > 0 {
+            return;
+        }
+        self.health = self.health.saturating_sub(1);
+        self.hurt_timer = HURT_FRAMES;
+        self.knockback = match from {
+            Some(pos) => Facing::from_vector(self.col.position - pos).get_vector() * KNOCKBACK_SPEED,
+            None => Vector2D { x: 0, y: 0 },
+        };
+        if self.health == 0 {
+            self.free_ready = true;
+        }
+    }
+}
+
+impl GameObj for Enemy {
+    fn ready(&mut self) {
+        self.object.set_priority(self.get_priority());
+    }
+
+    fn update(&mut self, globals: &mut global_data::GlobalData) {
+        let cam_offset = globals.get_camera_offset();
+        self.col.position -= cam_offset;
+        self.origin -= cam_offset;
+        self.prev_pos = self.col.position;
+        self.timer = self.timer.wrapping_add(1);
+        if self.hurt_timer > 0 {
+            self.hurt_timer -= 1;
+        }
+        if self.hurt_timer > HURT_FRAMES - KNOCKBACK_FRAMES {
+            self.col.position += self.knockback;
+        } else {
+            self.run_behaviour(globals);
+        }
+        self.anim.update(globals);
+        self.object.set_sprite(self.anim.get_sprite());
+        self.object.set_hflip(self.facing == Facing::LEFT);
+        self.object.set_pos(self.col.position);
+        self.object.update_affine(globals);
+    }
+
+    fn check_to_free(&self) -> bool {
+        return self.free_ready;
+    }
+
+    fn check_collision(&mut self, other: &Box<dyn GameObj>) -> ResponseType {
+        let (Some(col_1), Some(col_2)) = (self.get_collider(), other.get_collider()) else {
+            return ResponseType::NONE;
+        };
+        if col_1.overlapping_rect(col_2).is_none() {
+            return ResponseType::NONE;
+        }
+        if other.check_response_type() == ResponseType::ATTACK {
+            self.take_hit(other.get_pos());
+        }
+        return self.check_response_type();
+    }
+
+    fn check_response_type(&self) -> ResponseType {
+        return ResponseType::DAMAGE;
+    }
+
+    fn get_collider(&self) -> Option<Rect<i32>> {
+        return Some(self.col);
+    }
+
+    fn get_pos(&self) -> Option<Vector2D<i32>> {
+        return Some(self.col.position);
+    }
+
+    fn get_hitboxes(&self) -> &[HitBox] {
+        return &HITBOXES;
+    }
+
+    fn on_hurt(&mut self, _hurtbox: &HitBox, _hitbox: &HitBox, attacker: &Box<dyn GameObj>) {
+        self.take_hit(attacker.get_pos());
+    }
+
+    fn get_prev_pos(&self) -> Option<Vector2D<i32>> {
+        return Some(self.prev_pos);
+    }
+
+    fn set_resolved_pos(&mut self, pos: Vector2D<i32>) {
+        self.col.position = pos;
+        self.object.set_pos(self.col.position);
+    }
+
+    fn is_pushable(&self) -> bool {
+        return true;
+    }
+
+    fn push(&mut self, offset: Vector2D<i32>) {
+        self.col.position += offset;
+        self.prev_pos += offset;
+        self.object.set_pos(self.col.position);
+    }
+
+    fn get_facing(&self) -> Option<Facing> {
+        return Some(self.facing);
+    }
+
+    fn y_sort(&self) -> bool {
+        return true;
+    }
+
+    fn draw(&self, frame: &mut GraphicsFrame) {
+        // Blinks while hurt.
+        if (self.hurt_timer / 4) % 2 == 1 {
+            return;
+        }
+        self.object.show(frame);
+    }
+}
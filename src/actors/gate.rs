@@ -0,0 +1,98 @@
+use agb::display::GraphicsFrame;
+use agb::fixnum::{Rect, Vector2D};
+use agb::include_aseprite;
+use crate::game_sprite::GameSprite;
+use crate::game_obj::{GameObj, ResponseType};
+use crate::global_data::{self, Flags};
+
+include_aseprite!(
+    mod sprites,
+    "gfx/new_img.aseprite"
+);
+
+// Uses the placeholder wall sprite until door sprites are drawn.
+#[derive(Clone, Copy)]
+pub(crate) enum GateType {
+    DOOR, // A solid wall until the flag is set, then it opens.
+    BARRIER, // Appears as a solid wall once the flag is set. (example: a trap closing behind the player)
+    BRIDGE // A pit that blocks like a wall until the flag is set, then a bridge appears over it, drawn under anything walking across.
+}
+
+pub(crate) struct Gate {
+    object: GameSprite,
+    col: Rect<i32>,
+    gate_type: GateType,
+    flag: Flags,
+    present: bool
+}
+
+impl Gate {
+    pub fn new(starting_pos: Vector2D<i32>, gate_type: GateType, flag: Flags) -> Gate {
+        Gate {
+            object: GameSprite::new(sprites::WALL.sprite(0)),
+            col: Rect { position: starting_pos, size: Vector2D { x: 16, y: 16 } },
+            gate_type,
+            flag,
+            present: false
+        }
+    }
+
+    fn is_solid(&self) -> bool {
+        match self.gate_type {
+            GateType::DOOR | GateType::BARRIER => return self.present,
+            GateType::BRIDGE => return !self.present,
+        }
+    }
+}
+
+impl GameObj for Gate {
+    fn ready(&mut self) {
+        self.object.set_priority(self.get_priority());
+    }
+
+    fn update(&mut self, globals: &mut global_data::GlobalData) {
+        let flag_set = globals.check_flag(self.flag);
+        self.present = match self.gate_type {
+            GateType::DOOR => !flag_set,
+            GateType::BARRIER | GateType::BRIDGE => flag_set,
+        };
+        self.col.position -= globals.get_camera_offset();
+        self.object.set_pos(self.col.position);
+        self.object.update_affine(globals);
+    }
+
+    fn check_response_type(&self) -> ResponseType {
+        if self.is_solid() {
+            return ResponseType::WALL;
+        }
+        return ResponseType::NONE;
+    }
+
+    fn get_collider(&self) -> Option<Rect<i32>> {
+        if self.is_solid() {
+            return Some(self.col);
+        }
+        return None;
+    }
+
+    fn get_pos(&self) -> Option<Vector2D<i32>> {
+        return Some(self.col.position);
+    }
+
+    fn get_draw_layer(&self) -> i32 {
+        match self.gate_type {
+            GateType::BRIDGE => return -1,
+            _ => return 0,
+        }
+    }
+
+    fn y_sort(&self) -> bool {
+        return true;
+    }
+
+    fn draw(&self, frame: &mut GraphicsFrame) {
+        if self.present {
+            self.object.show(frame);
+        }
+    }
+}
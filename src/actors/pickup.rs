@@ -0,0 +1,112 @@
+use agb::display::GraphicsFrame;
+use agb::fixnum::{Rect, Vector2D};
+use agb::include_aseprite;
+use alloc::boxed::Box;
+use crate::collision::ColliderShape;
+use crate::game_sprite::GameSprite;
+use crate::game_obj::{GameObj, ResponseType};
+use crate::global_data;
+use crate::inventory::Items;
+use crate::sfx::SFX;
+
+include_aseprite!(
+    mod sprites,
+    "gfx/new_img.aseprite"
+);
+
+// Uses the placeholder wall sprite until pickup sprites are drawn, the collider is the middle of it.
+const SIZE: i32 = 8;
+const SPRITE_OFFSET: Vector2D<i32> = Vector2D { x: 4, y: 4 };
+
+#[derive(Clone, Copy)]
+pub(crate) enum PickupType {
+    ITEM(Items, u16), // Gives the amount of the item.
+    HEALTH(u8) // Heals the player by the amount.
+}
+
+pub(crate) struct Pickup {
+    object: GameSprite,
+    col: Rect<i32>,
+    pickup_type: PickupType,
+    touched: bool,
+    free_ready: bool
+}
+
+impl Pickup {
+    pub fn new(starting_pos: Vector2D<i32>, pickup_type: PickupType) -> Pickup {
+        Pickup {
+            object: GameSprite::new(sprites::WALL.sprite(0)),
+            col: Rect { position: starting_pos, size: Vector2D { x: SIZE, y: SIZE } },
+            pickup_type,
+            touched: false,
+            free_ready: false
+        }
+    }
+
+    fn collect(&mut self, globals: &mut global_data::GlobalData) {
+        // Stays in place if the player can not take it, such as at full health or holding a full stack.
+        let collected = match self.pickup_type {
+            PickupType::ITEM(item, amount) => globals.give_item(item, amount) > 0,
+            PickupType::HEALTH(amount) => {
+                let can_heal = globals.get_player_health() < globals.get_player_max_health();
+                globals.heal_player(amount);
+                can_heal
+            },
+        };
+        if collected {
+            globals.play_sfx(SFX::Blip);
+            self.free_ready = true;
+        }
+    }
+}
+
+impl GameObj for Pickup {
+    fn ready(&mut self) {
+        self.object.set_priority(self.get_priority());
+    }
+
+    fn update(&mut self, globals: &mut global_data::GlobalData) {
+        // The effect is applied here as check_collision() has no access to the globals.
+        if self.touched {
+            self.collect(globals);
+            self.touched = false;
+        }
+        self.col.position -= globals.get_camera_offset();
+        self.object.set_pos(self.col.position - SPRITE_OFFSET);
+        self.object.update_affine(globals);
+    }
+
+    fn check_to_free(&self) -> bool {
+        return self.free_ready;
+    }
+
+    fn check_collision(&mut self, other: &Box<dyn GameObj>) -> ResponseType {
+        let (Some(col_1), Some(col_2)) = (self.get_collider(), other.get_collider()) else {
+            return ResponseType::NONE;
+        };
+        if col_1.overlapping_rect(col_2).is_some() && other.check_response_type() == ResponseType::PLAYER {
+            self.touched = true;
+        }
+        return ResponseType::NONE;
+    }
+
+    fn check_response_type(&self) -> ResponseType {
+        return ResponseType::PICKUP;
+    }
+
+    fn get_collider(&self) -> Option<Rect<i32>> {
+        return Some(self.col);
+    }
+
+    fn get_collider_shape(&self) -> ColliderShape {
+        return ColliderShape::CIRCLE;
+    }
+
+    fn get_pos(&self) -> Option<Vector2D<i32>> {
+        return Some(self.col.position);
+    }
+
+    fn draw(&self, frame: &mut GraphicsFrame) {
+        self.object.show(frame);
+    }
+}
@@ -0,0 +1,136 @@
+use agb::display::GraphicsFrame;
+use agb::fixnum::{Num, Rect, Vector2D};
+use agb::include_aseprite;
+use crate::meta_sprite::MetaSprite;
+use crate::game_obj::{GameObj, ResponseType};
+use crate::global_data;
+
+include_aseprite!(
+    mod sprites,
+    "gfx/new_img.aseprite"
+);
+
+// Built from the placeholder wall sprite, one part per 16 pixel tile of width.
+const TILE_SIZE: i32 = 16;
+
+#[derive(Clone, Copy)]
+pub(crate) enum PlatformPath {
+    LINEAR(Vector2D<i32>, u16), // Moves by the offset over the given frames, then stays there.
+    PINGPONG(Vector2D<i32>, u16), // Moves by the offset over the given frames, then back again.
+    CIRCULAR(i32, u16) // Circles with the given radius starting from where it was placed, taking the given frames per lap.
+}
+
+pub(crate) struct Platform {
+    sprite: MetaSprite,
+    col: Rect<i32>,
+    origin: Vector2D<i32>,
+    path: PlatformPath,
+    path_offset: Vector2D<i32>,
+    motion: Vector2D<i32>,
+    timer: u32
+}
+
+impl Platform {
+    /// Position, path, and width in 16 pixel tiles.
+    pub fn new(starting_pos: Vector2D<i32>, path: PlatformPath, width: u8) -> Platform {
+        let mut sprite = MetaSprite::new();
+        for tile in 0..(width.max(1) as i32) {
+            sprite.add_part(sprites::WALL.sprite(0), Vector2D { x: tile * TILE_SIZE, y: 0 });
+        }
+        sprite.set_pos(starting_pos);
+        Platform {
+            col: sprite.get_collider(),
+            sprite,
+            origin: starting_pos,
+            path,
+            path_offset: Vector2D { x: 0, y: 0 },
+            motion: Vector2D { x: 0, y: 0 },
+            timer: 0
+        }
+    }
+
+    fn get_path_offset(&self) -> Vector2D<i32> {
+        match self.path {
+            PlatformPath::LINEAR(offset, frames) => {
+                let frames = frames.max(1) as u32;
+                return offset * self.timer.min(frames) as i32 / frames as i32;
+            },
+            PlatformPath::PINGPONG(offset, frames) => {
+                let frames = frames.max(1) as u32;
+                let mut step = self.timer % (frames * 2);
+                if step > frames {
+                    step = frames * 2 - step;
+                }
+                return offset * step as i32 / frames as i32;
+            },
+            PlatformPath::CIRCULAR(radius, frames) => {
+                let frames = frames.max(1) as u32;
+                let angle: Num<i32, 8> = Num::new((self.timer % frames) as i32) / frames as i32;
+                return Vector2D {
+                    x: ((angle.cos() - 1) * radius).floor(),
+                    y: (angle.sin() * radius).floor()
+                };
+            },
+        }
+    }
+
+    fn set_pos(&mut self) {
+        self.col.position = self.origin + self.path_offset;
+        self.sprite.set_pos(self.col.position);
+    }
+}
+
+impl GameObj for Platform {
+    fn ready(&mut self) {
+        self.sprite.set_priority(self.get_priority());
+    }
+
+    fn update(&mut self, globals: &mut global_data::GlobalData) {
+        self.origin -= globals.get_camera_offset();
+        self.timer = self.timer.wrapping_add(1);
+        let next_offset = self.get_path_offset();
+        self.motion = next_offset - self.path_offset;
+        self.path_offset = next_offset;
+        self.set_pos();
+    }
+
+    fn on_screen(&self) -> bool {
+        // Platforms can be wider than the default check allows for.
+        let inside_x_range = (self.col.position.x + self.col.size.x > 0) && (self.col.position.x < agb::display::WIDTH);
+        let inside_y_range = (self.col.position.y + self.col.size.y > 0) && (self.col.position.y < agb::display::HEIGHT);
+        return inside_x_range && inside_y_range;
+    }
+
+    fn check_response_type(&self) -> ResponseType {
+        return ResponseType::WALL;
+    }
+
+    fn get_collider(&self) -> Option<Rect<i32>> {
+        return Some(self.col);
+    }
+
+    fn get_pos(&self) -> Option<Vector2D<i32>> {
+        return Some(self.col.position);
+    }
+
+    fn get_motion(&self) -> Option<Vector2D<i32>> {
+        return Some(self.motion);
+    }
+
+    fn stop_motion(&mut self) {
+        // Goes back to where it was at the start of the frame, and tries again next frame.
+        self.timer = self.timer.wrapping_sub(1);
+        self.path_offset -= self.motion;
+        self.motion = Vector2D { x: 0, y: 0 };
+        self.set_pos();
+    }
+
+    fn get_draw_layer(&self) -> i32 {
+        // Drawn under anything riding on it.
+        return -1;
+    }
+
+    fn draw(&self, frame: &mut GraphicsFrame) {
+        self.sprite.show(frame);
+    }
+}
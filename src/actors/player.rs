@@ -1,10 +1,14 @@
-use agb::fixnum::{vec2, Rect};
-use agb::input::Button;
+use agb::fixnum::{num, vec2, Num, Rect};
 use agb::{display::GraphicsFrame, fixnum::Vector2D};
 use agb::include_aseprite;
-use agb::display::object::Object;
 use alloc::boxed::Box;
-use crate::game_obj::{GameObj, ResponseType};
+use crate::animation::{AnimMode, Animation, AnimationPlayer};
+use crate::collision::{BoxType, HitBox, HitLayer};
+use crate::game_sprite::GameSprite;
+use crate::game_obj::{Facing, GameObj, ResponseType};
+use crate::actor::projectile::{Owner, ProjectileRequest};
+use crate::input_map::Actions;
+use crate::sfx::SFX;
 use crate::{actor, global_data};
 
 include_aseprite!(
@@ -24,68 +28,172 @@ const RIGHT_SCROLL: i32 = 209;
 const UP_SCROLL: i32 = 15;
 const DOWN_SCROLL: i32 = 124;
 
+// Side animations face right and are flipped when facing left.
+// All of these use the placeholder player sprite until directional frames are drawn.
+const IDLE_DOWN_ANIM: Animation = Animation::new(&sprites::TEST_PLAYER, &[16], AnimMode::LOOP);
+const IDLE_UP_ANIM: Animation = Animation::new(&sprites::TEST_PLAYER, &[16], AnimMode::LOOP);
+const IDLE_SIDE_ANIM: Animation = Animation::new(&sprites::TEST_PLAYER, &[16], AnimMode::LOOP);
+const WALK_DOWN_ANIM: Animation = Animation::new(&sprites::TEST_PLAYER, &[8], AnimMode::LOOP);
+const WALK_UP_ANIM: Animation = Animation::new(&sprites::TEST_PLAYER, &[8], AnimMode::LOOP);
+const WALK_SIDE_ANIM: Animation = Animation::new(&sprites::TEST_PLAYER, &[8], AnimMode::LOOP);
+const SWING_ANIM: Animation = Animation::new(&sprites::TEST_PLAYER, &[12], AnimMode::ONCE);
+
+// The sword only hits on these frames of the swing animation, the placeholder only has the one.
+const SWING_HIT_FRAMES: &[usize] = &[0];
+const BODY: HitBox = HitBox::new("body", BoxType::HURTBOX, HitLayer::PLAYER, vec2(0, 0), vec2(16, 16));
+const BODY_BOXES: [HitBox; 1] = [BODY];
+const SWING_UP_BOXES: [HitBox; 2] = [BODY, HitBox::new("sword", BoxType::HITBOX, HitLayer::PLAYER, vec2(0, -16), vec2(16, 16))];
+const SWING_DOWN_BOXES: [HitBox; 2] = [BODY, HitBox::new("sword", BoxType::HITBOX, HitLayer::PLAYER, vec2(0, 16), vec2(16, 16))];
+const SWING_LEFT_BOXES: [HitBox; 2] = [BODY, HitBox::new("sword", BoxType::HITBOX, HitLayer::PLAYER, vec2(-16, 0), vec2(16, 16))];
+const SWING_RIGHT_BOXES: [HitBox; 2] = [BODY, HitBox::new("sword", BoxType::HITBOX, HitLayer::PLAYER, vec2(16, 0), vec2(16, 16))];
+
+// consts related to taking damage
+const HURT_FRAMES: u16 = 60; // How long the player is invincible for after a hit.
+const KNOCKBACK_FRAMES: u16 = 8;
+const KNOCKBACK_SPEED: i32 = 2;
+const DEATH_FRAMES: u16 = 60;
+const SHOT_SPEED: i32 = 3;
+
 pub(crate) struct Player {
-    object: Object,
+    object: GameSprite,
+    anim: AnimationPlayer,
     col: Rect<i32>,
     prev_pos: Vector2D<i32>,
     off_screen_pos: Vector2D<i32>,
-    moving: [bool; 4],
+    facing: Facing,
+    walking: bool,
+    swinging: bool,
+    hurt_timer: u16,
+    pending_damage: u8,
+    knockback: Vector2D<i32>,
+    death_timer: u16,
     free_ready: bool
 }
 
 impl Player {
     pub fn new(starting_pos: Vector2D<i32>) -> Player {
         Player {
-            object: Object::new(sprites::TEST_PLAYER.sprite(0)),
+            object: GameSprite::new(sprites::TEST_PLAYER.sprite(0)),
+            anim: AnimationPlayer::new(IDLE_DOWN_ANIM),
             col: Rect { position: starting_pos, size: vec2(16, 16) },
             prev_pos: Vector2D { x: 0, y: 0 },
             off_screen_pos: Vector2D { x: 0, y: 0 },
-            moving: [false, false, false, false],
+            facing: Facing::DOWN,
+            walking: false,
+            swinging: false,
+            hurt_timer: 0,
+            pending_damage: 0,
+            knockback: Vector2D { x: 0, y: 0 },
+            death_timer: 0,
             free_ready: false
         }
     }
 
     fn handle_input(&mut self, globals: &mut global_data::GlobalData) {
-        let controller = globals.get_input();
+        // Stands still until a swing is finished.
+        if self.swinging {
+            return;
+        }
         let speed = 1;
-        if controller.is_pressed(Button::UP) {
-            self.col.position.y -= speed;
-        } else if controller.is_pressed(Button::DOWN) {
-            self.col.position.y += speed;
+        let move_dir = globals.get_move_vector();
+        self.col.position += move_dir * speed;
+        self.update_facing(move_dir);
+
+        if globals.is_action_just_pressed(Actions::Attack) {
+            // Fires from the center of the player in the direction they are facing.
+            let spawn_pos = self.col.position + vec2(4, 4);
+            let velocity = self.facing.get_vector() * SHOT_SPEED;
+            globals.spawn_queue(actor::Actor::AProjectile(ProjectileRequest::from_owner(spawn_pos, velocity, Owner::PLAYER)));
+            globals.play_sfx(SFX::Blip);
+        } else if globals.is_action_just_pressed(Actions::Swing) {
+            self.swinging = true;
+            globals.play_sfx(SFX::Blip);
+        } else if globals.is_action_just_pressed(Actions::Cancel) {
+            globals.queue_scene_transition(crate::scene::SCENES::Map001);
         }
+    }
 
-        if controller.is_pressed(Button::LEFT) {
-            self.col.position.x -= speed;
-        } else if controller.is_pressed(Button::RIGHT) {
-            self.col.position.x += speed;
+    fn take_hit(&mut self, from: Option<Vector2D<i32>>) {
+        // Called from check_collision(), the damage is applied to the globals on the next update.
+        if self.hurt_timer > 0 || self.death_timer > 0 {
+            return;
         }
+        self.pending_damage += 1;
+        self.hurt_timer = HURT_FRAMES;
+        self.knockback = match from {
+            Some(pos) => Facing::from_vector(self.col.position - pos).get_vector() * KNOCKBACK_SPEED,
+            None => Vector2D { x: 0, y: 0 },
+        };
+    }
 
-        if controller.is_just_pressed(Button::A) {
-            //globals.queue_scene_transition(crate::scene::SCENES::TestScene);
-            let mut new_pos = self.col.position.clone();
-            new_pos.x += 16;
-            globals.spawn_queue(actor::Actor::AWall(new_pos));
-        } else if controller.is_just_pressed(Button::B) {
-            globals.queue_scene_transition(crate::scene::SCENES::Map001);
+    fn apply_damage(&mut self, globals: &mut global_data::GlobalData) {
+        if self.pending_damage == 0 {
+            return;
+        }
+        globals.damage_player(self.pending_damage);
+        globals.play_sfx(SFX::Blip);
+        self.pending_damage = 0;
+        if globals.get_player_health() == 0 {
+            self.death_timer = DEATH_FRAMES;
+            self.hurt_timer = 0;
         }
     }
 
-    fn prevent_movement(&mut self) {
-        // Prevents movement when reacting to a wall, also offsets during a scroll to prevent clipping.
-        let mut additional_pushback: Vector2D<i32> = Vector2D { x: 0, y: 0 };
+    fn update_death(&mut self, globals: &mut global_data::GlobalData) {
+        // Spins and shrinks away, then respawns once the death sequence ends.
+        self.death_timer -= 1;
+        let progress: Num<i32, 8> = Num::new((DEATH_FRAMES - self.death_timer) as i32) / (DEATH_FRAMES as i32);
+        self.object.set_rotation(progress * 2);
+        self.object.set_scale(vec2(num!(1) - progress, num!(1) - progress));
+        if self.death_timer == 0 {
+            globals.respawn_player();
+        }
+    }
 
-        if self.moving[0] {
-            additional_pushback.x -= 2;
-        } else if self.moving[1] {
-            additional_pushback.x += 2;
+    fn update_facing(&mut self, move_dir: Vector2D<i32>) {
+        // Keeps the current facing while its direction is still held, so moving diagonally does not flicker between directions.
+        self.walking = move_dir != Vector2D { x: 0, y: 0 };
+        let still_held = match self.facing {
+            Facing::UP => move_dir.y < 0,
+            Facing::DOWN => move_dir.y > 0,
+            Facing::LEFT => move_dir.x < 0,
+            Facing::RIGHT => move_dir.x > 0,
+        };
+        if !self.walking || still_held {
+            return;
+        }
+        if move_dir.x < 0 {
+            self.facing = Facing::LEFT;
+        } else if move_dir.x > 0 {
+            self.facing = Facing::RIGHT;
+        } else if move_dir.y < 0 {
+            self.facing = Facing::UP;
+        } else {
+            self.facing = Facing::DOWN;
         }
+    }
 
-        if self.moving[2] {
-            additional_pushback.y -= 2;
-        } else if self.moving[3] {
-            additional_pushback.y += 2;
+    fn update_animation(&mut self, globals: &mut global_data::GlobalData) {
+        let next_anim = match (self.facing, self.walking) {
+            _ if self.swinging => SWING_ANIM,
+            (Facing::UP, false) => IDLE_UP_ANIM,
+            (Facing::DOWN, false) => IDLE_DOWN_ANIM,
+            (Facing::LEFT | Facing::RIGHT, false) => IDLE_SIDE_ANIM,
+            (Facing::UP, true) => WALK_UP_ANIM,
+            (Facing::DOWN, true) => WALK_DOWN_ANIM,
+            (Facing::LEFT | Facing::RIGHT, true) => WALK_SIDE_ANIM,
+        };
+        self.anim.play(next_anim);
+        self.anim.update(globals);
+        if self.swinging && self.anim.is_finished() {
+            self.swinging = false;
         }
-        self.col.position = self.prev_pos - additional_pushback;
+        self.object.set_sprite(self.anim.get_sprite());
+        self.object.set_hflip(self.facing == Facing::LEFT);
+    }
+
+    fn sword_active(&self) -> bool {
+        return self.swinging && SWING_HIT_FRAMES.contains(&self.anim.get_frame());
     }
 
     fn move_camera_offset(&mut self, globals: &mut global_data::GlobalData) {
@@ -94,21 +202,17 @@ impl Player {
         if (self.col.position.x < LEFT_SCROLL) && (self.off_screen_pos.x > LEFT_EDGE) {
             pos_offset.x -= 1;
             self.off_screen_pos.x -= 1;
-            self.moving[0] = true;
         } else if (self.col.position.x > RIGHT_SCROLL) && (self.off_screen_pos.x < RIGHT_EDGE) {
             pos_offset.x += 1;
             self.off_screen_pos.x += 1;
-            self.moving[1] = true;
         }
 
         if (self.col.position.y < UP_SCROLL) && (self.off_screen_pos.y < UP_EDGE) {
             pos_offset.y -= 1;
             self.off_screen_pos.y += 1;
-            self.moving[2] = true;
         } else if (self.col.position.y > DOWN_SCROLL) && (self.off_screen_pos.y > DOWN_EDGE) {
             pos_offset.y += 1;
             self.off_screen_pos.y -= 1;
-            self.moving[3] = true;
         }
         globals.set_camera_offset(pos_offset);
     } 
@@ -116,18 +220,36 @@ impl Player {
 }
 
 impl GameObj for Player {
+    fn ready(&mut self) {
+        self.object.set_priority(self.get_priority());
+    }
+
     fn update(&mut self, globals: &mut global_data::GlobalData) {
-        self.moving[0] = false;
-        self.moving[1] = false;
-        self.moving[2] = false;
-        self.moving[3] = false;
+        if let Some(respawn_pos) = globals.take_respawn_pos() {
+            self.col.position = respawn_pos;
+        }
         self.prev_pos = self.col.position;
         self.col.position.x = self.col.position.x.clamp(0, agb::display::WIDTH - 16);
         self.col.position.y = self.col.position.y.clamp(0, agb::display::HEIGHT - 16);
-        self.handle_input(globals);
+        self.apply_damage(globals);
+        if self.death_timer > 0 {
+            self.update_death(globals);
+        } else if self.hurt_timer > HURT_FRAMES - KNOCKBACK_FRAMES {
+            self.col.position += self.knockback;
+        } else {
+            self.handle_input(globals);
+        }
+        if self.hurt_timer > 0 {
+            self.hurt_timer -= 1;
+        }
         self.move_camera_offset(globals);
+        // The last position is moved with the camera too, so walls only stop the players own movement.
         self.col.position -= globals.get_camera_offset();
+        self.prev_pos -= globals.get_camera_offset();
+        self.update_animation(globals);
         self.object.set_pos(self.col.position);
+        self.object.update_affine(globals);
+        globals.set_player_pos(Some(self.col.position));
     }
 
     fn on_screen(&self) -> bool {
@@ -150,10 +272,9 @@ impl GameObj for Player {
         };
         match col_1.overlapping_rect(col_2) {
             Some(_) => {
-                match other.check_response_type() {
-                    // Our object handling the collision locally.
-                    ResponseType::WALL => self.prevent_movement(),
-                    _ => { }, //Unhandled collision type
+                // Our object handling the collision locally, walls are handled by the collision resolver.
+                if other.check_response_type() == ResponseType::DAMAGE {
+                    self.take_hit(other.get_pos());
                 }
                 return self.check_response_type();
             },
@@ -175,7 +296,55 @@ impl GameObj for Player {
         return Some(self.col.position);
     }
 
+    fn get_hitboxes(&self) -> &[HitBox] {
+        if !self.sword_active() {
+            return &BODY_BOXES;
+        }
+        match self.facing {
+            Facing::UP => return &SWING_UP_BOXES,
+            Facing::DOWN => return &SWING_DOWN_BOXES,
+            Facing::LEFT => return &SWING_LEFT_BOXES,
+            Facing::RIGHT => return &SWING_RIGHT_BOXES,
+        }
+    }
+
+    fn on_hurt(&mut self, _hurtbox: &HitBox, _hitbox: &HitBox, attacker: &Box<dyn GameObj>) {
+        self.take_hit(attacker.get_pos());
+    }
+
+    fn get_prev_pos(&self) -> Option<Vector2D<i32>> {
+        return Some(self.prev_pos);
+    }
+
+    fn set_resolved_pos(&mut self, pos: Vector2D<i32>) {
+        self.col.position = pos;
+        self.object.set_pos(self.col.position);
+    }
+
+    fn is_pushable(&self) -> bool {
+        return true;
+    }
+
+    fn push(&mut self, offset: Vector2D<i32>) {
+        // Moves the last position too, so walls do not undo being carried.
+        self.col.position += offset;
+        self.prev_pos += offset;
+        self.object.set_pos(self.col.position);
+    }
+
+    fn get_facing(&self) -> Option<Facing> {
+        return Some(self.facing);
+    }
+
+    fn y_sort(&self) -> bool {
+        return true;
+    }
+
     fn draw(&self, frame: &mut GraphicsFrame) {
+        // Blinks while invincible.
+        if (self.hurt_timer / 4) % 2 == 1 {
+            return;
+        }
         self.object.show(frame);
     }
 }
\ No newline at end of file
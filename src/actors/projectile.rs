@@ -0,0 +1,176 @@
+use agb::display::GraphicsFrame;
+use agb::fixnum::{Rect, Vector2D};
+use agb::include_aseprite;
+use alloc::{boxed::Box, vec::Vec};
+use crate::collision::{BoxType, HitLayer};
+use crate::game_sprite::GameSprite;
+use crate::game_obj::{GameObj, ResponseType};
+use crate::global_data;
+
+include_aseprite!(
+    mod sprites,
+    "gfx/new_img.aseprite"
+);
+
+// Uses the placeholder wall sprite until projectile sprites are drawn, the collider is the middle of it.
+const SIZE: i32 = 8;
+const SPRITE_OFFSET: Vector2D<i32> = Vector2D { x: 4, y: 4 };
+const DEFAULT_LIFETIME: u16 = 120;
+const HITSCAN_LIFETIME: u16 = 1;
+
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Owner {
+    PLAYER, // Hits enemies, sent as ATTACK.
+    ENEMY // Hits the player, sent as DAMAGE.
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct ProjectileRequest {
+    pos: Vector2D<i32>,
+    velocity: Vector2D<i32>,
+    owner: Owner,
+    lifetime: u16,
+    pierce: u8
+}
+
+impl ProjectileRequest {
+    // The lifetime is how many frames it lasts, pierce is how many targets it can pass through before it is used up.
+    pub fn new(pos: Vector2D<i32>, velocity: Vector2D<i32>, owner: Owner, lifetime: u16, pierce: u8) -> ProjectileRequest {
+        ProjectileRequest {
+            pos,
+            velocity,
+            owner,
+            lifetime,
+            pierce
+        }
+    }
+
+    pub fn from_owner(pos: Vector2D<i32>, velocity: Vector2D<i32>, owner: Owner) -> ProjectileRequest {
+        return ProjectileRequest::new(pos, velocity, owner, DEFAULT_LIFETIME, 0);
+    }
+
+    pub fn hitscan(hit_point: Vector2D<i32>, owner: Owner) -> ProjectileRequest {
+        // A still projectile centered on where a cast hit, lasting a single frame. (example: the point from globals.cast_segment())
+        return ProjectileRequest::new(hit_point - SPRITE_OFFSET, Vector2D { x: 0, y: 0 }, owner, HITSCAN_LIFETIME, 0);
+    }
+}
+
+pub(crate) struct Projectile {
+    object: GameSprite,
+    col: Rect<i32>,
+    velocity: Vector2D<i32>,
+    owner: Owner,
+    lifetime: u16,
+    pierce: u8,
+    touching: Vec<u32>, // The ids of the targets touched this frame.
+    was_touching: Vec<u32>,
+    free_ready: bool
+}
+
+impl Projectile {
+    pub fn new(request: ProjectileRequest) -> Projectile {
+        Projectile {
+            object: GameSprite::new(sprites::WALL.sprite(0)),
+            col: Rect { position: request.pos, size: Vector2D { x: SIZE, y: SIZE } },
+            velocity: request.velocity,
+            owner: request.owner,
+            lifetime: request.lifetime,
+            pierce: request.pierce,
+            touching: Vec::new(),
+            was_touching: Vec::new(),
+            free_ready: false
+        }
+    }
+
+    fn is_target(&self, other: &Box<dyn GameObj>) -> bool {
+        // Only reacts to the other side, so projectiles never hit their owner.
+        // Enemy shots are sent as DAMAGE too, so player shots also need an enemy hurtbox to pass through them.
+        match self.owner {
+            Owner::PLAYER => return other.check_response_type() == ResponseType::DAMAGE
+                && other.get_hitboxes().iter().any(|hitbox| hitbox.box_type == BoxType::HURTBOX && hitbox.layer == HitLayer::ENEMY),
+            Owner::ENEMY => return other.check_response_type() == ResponseType::PLAYER,
+        }
+    }
+
+    fn hit_target(&mut self, id: u32) {
+        // Counts a hit when it starts touching a target, so passing through one only uses up one pierce.
+        // Each target is tracked by id, so moving straight into an overlapping neighbour still counts it.
+        if self.touching.contains(&id) {
+            return;
+        }
+        self.touching.push(id);
+        if self.was_touching.contains(&id) {
+            return;
+        }
+        if self.pierce == 0 {
+            self.free_ready = true;
+        } else {
+            self.pierce -= 1;
+        }
+    }
+}
+
+impl GameObj for Projectile {
+    fn ready(&mut self) {
+        self.object.set_priority(self.get_priority());
+    }
+
+    fn update(&mut self, globals: &mut global_data::GlobalData) {
+        self.was_touching = core::mem::take(&mut self.touching);
+        self.lifetime = self.lifetime.saturating_sub(1);
+        if self.lifetime == 0 {
+            self.free_ready = true;
+        }
+        self.col.position += self.velocity;
+        self.col.position -= globals.get_camera_offset();
+        self.object.set_pos(self.col.position - SPRITE_OFFSET);
+        self.object.update_affine(globals);
+    }
+
+    fn simple_update(&mut self, _globals: &mut global_data::GlobalData) {
+        // Projectiles are done with once they leave the screen.
+        self.free_ready = true;
+    }
+
+    fn check_to_free(&self) -> bool {
+        return self.free_ready;
+    }
+
+    fn can_cleanup(&self) -> bool {
+        return true;
+    }
+
+    fn check_collision_with_id(&mut self, other: &Box<dyn GameObj>, other_id: u32) -> ResponseType {
+        let (Some(col_1), Some(col_2)) = (self.get_collider(), other.get_collider()) else {
+            return ResponseType::NONE;
+        };
+        if col_1.overlapping_rect(col_2).is_none() {
+            return ResponseType::NONE;
+        }
+        if other.check_response_type() == ResponseType::WALL {
+            self.free_ready = true;
+        } else if self.is_target(other) {
+            self.hit_target(other_id);
+        }
+        return ResponseType::NONE;
+    }
+
+    fn check_response_type(&self) -> ResponseType {
+        match self.owner {
+            Owner::PLAYER => return ResponseType::ATTACK,
+            Owner::ENEMY => return ResponseType::DAMAGE,
+        }
+    }
+
+    fn get_collider(&self) -> Option<Rect<i32>> {
+        return Some(self.col);
+    }
+
+    fn get_pos(&self) -> Option<Vector2D<i32>> {
+        return Some(self.col.position);
+    }
+
+    fn draw(&self, frame: &mut GraphicsFrame) {
+        self.object.show(frame);
+    }
+}
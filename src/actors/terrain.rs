@@ -0,0 +1,94 @@
+use agb::display::GraphicsFrame;
+use agb::fixnum::{Rect, Vector2D};
+use agb::include_aseprite;
+use crate::collision::{ColliderShape, Slope};
+use crate::meta_sprite::MetaSprite;
+use crate::game_obj::{GameObj, ResponseType};
+use crate::global_data;
+
+include_aseprite!(
+    mod sprites,
+    "gfx/new_img.aseprite"
+);
+
+// Built from the placeholder wall sprite, one part per 16 pixel tile of width, until slope sprites are drawn.
+const TILE_SIZE: i32 = 16;
+
+#[derive(Clone, Copy)]
+pub(crate) enum TerrainType {
+    RAMP(Slope), // A 45 degree slope, one tile wide.
+    LONGRAMP(Slope), // A 22.5 degree slope, two tiles wide.
+    LEDGE(u8) // A floor the given tiles wide that can be jumped through from below.
+}
+
+pub(crate) struct Terrain {
+    sprite: MetaSprite,
+    col: Rect<i32>,
+    terrain_type: TerrainType
+}
+
+impl Terrain {
+    pub fn new(starting_pos: Vector2D<i32>, terrain_type: TerrainType) -> Terrain {
+        let width = match terrain_type {
+            TerrainType::RAMP(_) => 1,
+            TerrainType::LONGRAMP(_) => 2,
+            TerrainType::LEDGE(tiles) => tiles.max(1) as i32,
+        };
+        let mut sprite = MetaSprite::new();
+        for tile in 0..width {
+            sprite.add_part(sprites::WALL.sprite(0), Vector2D { x: tile * TILE_SIZE, y: 0 });
+        }
+        sprite.set_pos(starting_pos);
+        Terrain {
+            col: sprite.get_collider(),
+            sprite,
+            terrain_type
+        }
+    }
+}
+
+impl GameObj for Terrain {
+    fn ready(&mut self) {
+        self.sprite.set_priority(self.get_priority());
+    }
+
+    fn update(&mut self, globals: &mut global_data::GlobalData) {
+        self.col.position -= globals.get_camera_offset();
+        self.sprite.set_pos(self.col.position);
+    }
+
+    fn on_screen(&self) -> bool {
+        // Long ramps and ledges can be wider than the default check allows for.
+        let inside_x_range = (self.col.position.x + self.col.size.x > 0) && (self.col.position.x < agb::display::WIDTH);
+        let inside_y_range = (self.col.position.y + self.col.size.y > 0) && (self.col.position.y < agb::display::HEIGHT);
+        return inside_x_range && inside_y_range;
+    }
+
+    fn check_response_type(&self) -> ResponseType {
+        return ResponseType::WALL;
+    }
+
+    fn get_collider(&self) -> Option<Rect<i32>> {
+        return Some(self.col);
+    }
+
+    fn get_collider_shape(&self) -> ColliderShape {
+        match self.terrain_type {
+            TerrainType::RAMP(slope) | TerrainType::LONGRAMP(slope) => return ColliderShape::SLOPE(slope),
+            TerrainType::LEDGE(_) => return ColliderShape::ONEWAY,
+        }
+    }
+
+    fn get_pos(&self) -> Option<Vector2D<i32>> {
+        return Some(self.col.position);
+    }
+
+    fn get_draw_layer(&self) -> i32 {
+        // Drawn under anything standing on it.
+        return -1;
+    }
+
+    fn draw(&self, frame: &mut GraphicsFrame) {
+        self.sprite.show(frame);
+    }
+}
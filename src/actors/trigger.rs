@@ -0,0 +1,177 @@
+use agb::display::GraphicsFrame;
+use agb::fixnum::{Rect, Vector2D};
+use agb::include_aseprite;
+use alloc::boxed::Box;
+use crate::collision::{BoxType, HitBox, HitLayer};
+use crate::game_sprite::GameSprite;
+use crate::game_obj::{GameObj, ResponseType};
+use crate::global_data::{self, Flags};
+
+include_aseprite!(
+    mod sprites,
+    "gfx/new_img.aseprite"
+);
+
+// Levers can be hit by either side, so a enemy swinging at the player can flip one too.
+const LEVER_HITBOXES: [HitBox; 1] = [HitBox::new("body", BoxType::HURTBOX, HitLayer::WORLD, Vector2D { x: 0, y: 0 }, Vector2D { x: 16, y: 16 })];
+
+// Uses the placeholder wall sprite until switch sprites are drawn.
+#[derive(Clone, Copy)]
+pub(crate) enum TriggerType {
+    PLATE, // Sets the flag while the player stands on it, and clears it when they step off.
+    LEVER, // A solid switch that flips the flag each time it is shot or hit by a hitbox.
+    AREA(Vector2D<i32>) // A invisible area of the given size that sets the flag the first time the player enters it.
+}
+
+pub(crate) struct Trigger {
+    object: GameSprite,
+    col: Rect<i32>,
+    trigger_type: TriggerType,
+    flag: Flags,
+    touched: bool,
+    was_touched: bool,
+    free_ready: bool
+}
+
+impl Trigger {
+    pub fn new(starting_pos: Vector2D<i32>, trigger_type: TriggerType, flag: Flags) -> Trigger {
+        let size = match trigger_type {
+            TriggerType::AREA(size) => size,
+            _ => Vector2D { x: 16, y: 16 },
+        };
+        Trigger {
+            object: GameSprite::new(sprites::WALL.sprite(0)),
+            col: Rect { position: starting_pos, size },
+            trigger_type,
+            flag,
+            touched: false,
+            was_touched: false,
+            free_ready: false
+        }
+    }
+
+    fn update_flag(&mut self, globals: &mut global_data::GlobalData) {
+        // Touches are found in check_collision() or the sensor callbacks, then applied here on the next update.
+        // Sensors keep touched set until the player leaves, levers are touched again each frame they are hit.
+        let touched = self.touched;
+        if !self.is_sensor() {
+            self.touched = false;
+        }
+        match self.trigger_type {
+            TriggerType::PLATE => {
+                if touched != self.was_touched {
+                    globals.set_flag(self.flag, touched);
+                }
+            },
+            TriggerType::LEVER => {
+                if touched && !self.was_touched {
+                    globals.set_flag(self.flag, !globals.check_flag(self.flag));
+                }
+            },
+            TriggerType::AREA(_) => {
+                if touched {
+                    globals.set_flag(self.flag, true);
+                    self.free_ready = true;
+                }
+            },
+        }
+        self.was_touched = touched;
+    }
+}
+
+impl GameObj for Trigger {
+    fn ready(&mut self) {
+        self.object.set_priority(self.get_priority());
+    }
+
+    fn update(&mut self, globals: &mut global_data::GlobalData) {
+        self.update_flag(globals);
+        self.col.position -= globals.get_camera_offset();
+        self.object.set_hflip(globals.check_flag(self.flag));
+        self.object.set_pos(self.col.position);
+        self.object.update_affine(globals);
+    }
+
+    fn check_to_free(&self) -> bool {
+        return self.free_ready;
+    }
+
+    fn check_collision(&mut self, other: &Box<dyn GameObj>) -> ResponseType {
+        let (Some(col_1), Some(col_2)) = (self.get_collider(), other.get_collider()) else {
+            return ResponseType::NONE;
+        };
+        if col_1.overlapping_rect(col_2).is_none() {
+            return ResponseType::NONE;
+        }
+        if other.check_response_type() == ResponseType::ATTACK {
+            self.touched = true;
+        }
+        return ResponseType::NONE;
+    }
+
+    fn get_hitboxes(&self) -> &[HitBox] {
+        match self.trigger_type {
+            TriggerType::LEVER => return &LEVER_HITBOXES,
+            _ => return &[],
+        }
+    }
+
+    fn on_hurt(&mut self, _hurtbox: &HitBox, _hitbox: &HitBox, _attacker: &Box<dyn GameObj>) {
+        self.touched = true;
+    }
+
+    fn is_sensor(&self) -> bool {
+        // Plates and areas only watch for the player, so they never get in the way.
+        match self.trigger_type {
+            TriggerType::LEVER => return false,
+            _ => return true,
+        }
+    }
+
+    fn on_overlap_begin(&mut self, other: &Box<dyn GameObj>) {
+        if other.check_response_type() == ResponseType::PLAYER {
+            self.touched = true;
+        }
+    }
+
+    fn on_overlap_end(&mut self, other: Option<&Box<dyn GameObj>>) {
+        // The player is only freed when the scene changes, so anything freed while on the trigger is ignored.
+        if let Some(obj) = other && obj.check_response_type() == ResponseType::PLAYER {
+            self.touched = false;
+        }
+    }
+
+    fn check_response_type(&self) -> ResponseType {
+        match self.trigger_type {
+            TriggerType::LEVER => return ResponseType::WALL,
+            _ => return ResponseType::NONE,
+        }
+    }
+
+    fn get_collider(&self) -> Option<Rect<i32>> {
+        return Some(self.col);
+    }
+
+    fn get_pos(&self) -> Option<Vector2D<i32>> {
+        return Some(self.col.position);
+    }
+
+    fn get_draw_layer(&self) -> i32 {
+        // Plates are drawn under anything standing on them.
+        match self.trigger_type {
+            TriggerType::PLATE => return -1,
+            _ => return 0,
+        }
+    }
+
+    fn y_sort(&self) -> bool {
+        return true;
+    }
+
+    fn draw(&self, frame: &mut GraphicsFrame) {
+        if let TriggerType::AREA(_) = self.trigger_type {
+            return;
+        }
+        self.object.show(frame);
+    }
+}
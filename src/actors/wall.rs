@@ -1,8 +1,9 @@
-use agb::display::object::Object;
 use agb::display::GraphicsFrame;
 use agb::fixnum::Rect;
 use agb::fixnum::Vector2D;
 use agb::include_aseprite;
+use crate::animation::{AnimMode, Animation, AnimationPlayer};
+use crate::game_sprite::GameSprite;
 use crate::game_obj::{GameObj, ResponseType};
 
 include_aseprite!(
@@ -10,8 +11,11 @@ include_aseprite!(
     "gfx/new_img.aseprite"
 );
 
+const IDLE_ANIM: Animation = Animation::new(&sprites::WALL, &[8], AnimMode::LOOP);
+
 pub(crate) struct Wall {
-    object: Object,
+    object: GameSprite,
+    anim: AnimationPlayer,
     col: Rect<i32>,
     free_ready: bool
 }
@@ -19,7 +23,8 @@ pub(crate) struct Wall {
 impl Wall {
     pub fn new(starting_pos: Vector2D<i32>) -> Wall {
         Wall {
-            object: Object::new(sprites::WALL.sprite(0)),
+            object: GameSprite::new(sprites::WALL.sprite(0)),
+            anim: AnimationPlayer::new(IDLE_ANIM),
             col: Rect { position: starting_pos, size: Vector2D { x: 16, y: 16 } },
             free_ready: false
         }
@@ -27,9 +32,16 @@ impl Wall {
 }
 
 impl GameObj for Wall {
+    fn ready(&mut self) {
+        self.object.set_priority(self.get_priority());
+    }
+
     fn update(&mut self, globals: &mut crate::global_data::GlobalData) {
         self.col.position -= globals.get_camera_offset();
+        self.anim.update(globals);
+        self.object.set_sprite(self.anim.get_sprite());
         self.object.set_pos(self.col.position);
+        self.object.update_affine(globals);
     }
 
     fn check_to_free(&self) -> bool {
@@ -52,6 +64,10 @@ impl GameObj for Wall {
         return Some(self.col.position);
     }
 
+    fn y_sort(&self) -> bool {
+        return true;
+    }
+
     fn draw(&self, frame: &mut GraphicsFrame) {
         self.object.show(frame);  
     }
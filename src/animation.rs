@@ -0,0 +1,168 @@
+#![allow(dead_code, unused_variables)]
+
+/*
+    Sprite animation player, steps through the frames of a aseprite tag.
+    Actors hold a AnimationPlayer, call update() once per frame and then
+    set their objects sprite using get_sprite().
+*/
+
+use agb::display::object::{Sprite, Tag};
+use crate::global_data;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum AnimMode {
+    LOOP, // Restarts from the first frame after the last one.
+    PINGPONG, // Plays forwards then backwards, then repeats.
+    ONCE // Stops on the last frame and calls the finished callback.
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum AnimEvent {
+    NONE,
+    LOOPED,
+    FINISHED
+}
+
+#[derive(Clone, Copy)]
+pub struct Animation {
+    tag: &'static Tag,
+    frame_durations: &'static [u16],
+    mode: AnimMode
+}
+
+impl Animation {
+    // frame_durations is how many game frames each sprite frame is shown for.
+    // If there are less durations than frames, the last duration is used for the rest. (example: &[8] shows every frame for 8 game frames)
+    pub const fn new(tag: &'static Tag, frame_durations: &'static [u16], mode: AnimMode) -> Animation {
+        Animation {
+            tag,
+            frame_durations,
+            mode
+        }
+    }
+
+    pub fn get_frame_count(&self) -> usize {
+        return self.tag.sprites().len();
+    }
+
+    pub fn get_frame_duration(&self, frame: usize) -> u16 {
+        let duration = match self.frame_durations.get(frame) {
+            Some(duration) => *duration,
+            None => *self.frame_durations.last().unwrap_or(&1),
+        };
+        return duration.max(1);
+    }
+
+    pub fn get_mode(&self) -> AnimMode {
+        return self.mode;
+    }
+
+    fn is_same(&self, other: &Animation) -> bool {
+        return core::ptr::eq(self.tag, other.tag) && self.frame_durations == other.frame_durations && self.mode == other.mode;
+    }
+}
+
+pub struct AnimationPlayer {
+    animation: Animation,
+    frame: usize,
+    timer: u16,
+    reverse: bool,
+    finished: bool,
+    on_finished: Option<fn(&mut global_data::GlobalData)>
+}
+
+impl AnimationPlayer {
+    pub fn new(animation: Animation) -> AnimationPlayer {
+        AnimationPlayer {
+            animation,
+            frame: 0,
+            timer: 0,
+            reverse: false,
+            finished: false,
+            on_finished: None
+        }
+    }
+
+    pub fn play(&mut self, animation: Animation) {
+        // Switches to a new animation, does nothing if it is already playing so this can be called every frame.
+        if !self.animation.is_same(&animation) {
+            self.animation = animation;
+            self.restart();
+        }
+    }
+
+    pub fn restart(&mut self) {
+        self.frame = 0;
+        self.timer = 0;
+        self.reverse = false;
+        self.finished = false;
+    }
+
+    pub fn set_on_finished(&mut self, callback: Option<fn(&mut global_data::GlobalData)>) {
+        // Called once when a ONCE animation reaches the end of its last frame.
+        self.on_finished = callback;
+    }
+
+    pub fn update(&mut self, globals: &mut global_data::GlobalData) -> AnimEvent {
+        // Advances the animation by one game frame, returns if the animation looped or finished on this frame.
+        if self.finished {
+            return AnimEvent::NONE;
+        }
+        self.timer += 1;
+        if self.timer < self.animation.get_frame_duration(self.frame) {
+            return AnimEvent::NONE;
+        }
+        self.timer = 0;
+
+        let last_frame = self.animation.get_frame_count().saturating_sub(1);
+        match self.animation.get_mode() {
+            AnimMode::LOOP => {
+                if self.frame >= last_frame {
+                    self.frame = 0;
+                    return AnimEvent::LOOPED;
+                }
+                self.frame += 1;
+            },
+            AnimMode::PINGPONG => {
+                if last_frame == 0 {
+                    return AnimEvent::LOOPED;
+                }
+                if self.reverse {
+                    self.frame -= 1;
+                    if self.frame == 0 {
+                        self.reverse = false;
+                        return AnimEvent::LOOPED;
+                    }
+                } else {
+                    self.frame += 1;
+                    if self.frame >= last_frame {
+                        self.reverse = true;
+                    }
+                }
+            },
+            AnimMode::ONCE => {
+                if self.frame >= last_frame {
+                    self.finished = true;
+                    if let Some(callback) = self.on_finished {
+                        callback(globals);
+                    }
+                    return AnimEvent::FINISHED;
+                }
+                self.frame += 1;
+            },
+        }
+        return AnimEvent::NONE;
+    }
+
+    pub fn get_sprite(&self) -> &'static Sprite {
+        return self.animation.tag.sprite(self.frame);
+    }
+
+    pub fn get_frame(&self) -> usize {
+        return self.frame;
+    }
+
+    pub fn is_finished(&self) -> bool {
+        return self.finished;
+    }
+}
@@ -0,0 +1,251 @@
+#![allow(dead_code, unused_variables)]
+
+/*
+    Helpers for resolving collisions between object colliders, used by the GameState
+    so every object is pushed and blocked the same way.
+    Objects move freely in update(), then are moved back out of walls here using the
+    path they took from where they started the frame.
+    Colliders are a Rect, with a ColliderShape saying what part of it is solid.
+    Combat uses separate named HitBoxes, which never block anything.
+*/
+
+use agb::fixnum::{Rect, Vector2D};
+
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum ColliderShape {
+    RECT, // Fills the collider.
+    CIRCLE, // The largest circle that fits in the collider, its width is used as the size.
+    SLOPE(Slope), // The half of the collider under a diagonal, a square collider is a 45 degree slope and one twice as wide as it is tall is a 22.5 degree slope.
+    ONEWAY // Fills the collider, but only stops things landing on it from above.
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Slope {
+    RISING, // Goes up to the right.
+    FALLING // Goes down to the right.
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum HitLayer {
+    PLAYER,
+    ENEMY,
+    WORLD // Things like spikes or breakable pots, hit by and hitting both sides.
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum BoxType {
+    HURTBOX, // Can be hit by hitboxes on other layers.
+    HITBOX // Hits hurtboxes on other layers.
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct HitBox {
+    pub name: &'static str,
+    pub box_type: BoxType,
+    pub layer: HitLayer,
+    pub col: Rect<i32> // Relative to the position of the object it belongs to.
+}
+
+impl HitBox {
+    pub const fn new(name: &'static str, box_type: BoxType, layer: HitLayer, offset: Vector2D<i32>, size: Vector2D<i32>) -> HitBox {
+        HitBox {
+            name,
+            box_type,
+            layer,
+            col: Rect { position: offset, size }
+        }
+    }
+
+    pub fn hits(&self, pos: Vector2D<i32>, hurtbox: &HitBox, hurtbox_pos: Vector2D<i32>) -> bool {
+        // Checks this hitbox against a hurtbox on a different layer, both placed at their objects positions.
+        if self.box_type != BoxType::HITBOX || hurtbox.box_type != BoxType::HURTBOX || self.layer == hurtbox.layer {
+            return false;
+        }
+        let hit_col = Rect { position: self.col.position + pos, size: self.col.size };
+        let hurt_col = Rect { position: hurtbox.col.position + hurtbox_pos, size: hurtbox.col.size };
+        return hit_col.overlapping_rect(hurt_col).is_some();
+    }
+}
+
+pub fn shapes_overlap(col_1: Rect<i32>, shape_1: ColliderShape, col_2: Rect<i32>, shape_2: ColliderShape) -> bool {
+    // Checks the shapes themselves, anything past the bounding boxes never overlaps.
+    if col_1.overlapping_rect(col_2).is_none() {
+        return false;
+    }
+    match (shape_1, shape_2) {
+        (ColliderShape::CIRCLE, ColliderShape::CIRCLE) => return circles_overlap(col_1, col_2),
+        (ColliderShape::CIRCLE, ColliderShape::SLOPE(slope)) => return circle_overlaps_slope(col_1, col_2, slope),
+        (ColliderShape::SLOPE(slope), ColliderShape::CIRCLE) => return circle_overlaps_slope(col_2, col_1, slope),
+        (ColliderShape::CIRCLE, _) => return circle_overlaps_rect(col_1, col_2),
+        (_, ColliderShape::CIRCLE) => return circle_overlaps_rect(col_2, col_1),
+        (ColliderShape::SLOPE(_), ColliderShape::SLOPE(_)) => return true, // Slopes are only expected to touch other things, so their bounds are close enough.
+        (ColliderShape::SLOPE(slope), _) => return rect_overlaps_slope(col_2, col_1, slope),
+        (_, ColliderShape::SLOPE(slope)) => return rect_overlaps_slope(col_1, col_2, slope),
+        _ => return true,
+    }
+}
+
+// Circles are worked out at double scale, so their centers stay on whole numbers.
+fn get_doubled_center(col: Rect<i32>) -> Vector2D<i32> {
+    return col.position * 2 + col.size;
+}
+
+fn circles_overlap(circle_1: Rect<i32>, circle_2: Rect<i32>) -> bool {
+    let distance = get_doubled_center(circle_1) - get_doubled_center(circle_2);
+    let reach = circle_1.size.x + circle_2.size.x;
+    return distance.x * distance.x + distance.y * distance.y < reach * reach;
+}
+
+fn circle_overlaps_rect(circle: Rect<i32>, col: Rect<i32>) -> bool {
+    // Checks the point in the rect closest to the circles center.
+    let center = get_doubled_center(circle);
+    let closest = Vector2D {
+        x: center.x.clamp(col.position.x * 2, (col.position.x + col.size.x) * 2),
+        y: center.y.clamp(col.position.y * 2, (col.position.y + col.size.y) * 2)
+    };
+    let distance = center - closest;
+    return distance.x * distance.x + distance.y * distance.y < circle.size.x * circle.size.x;
+}
+
+fn get_slope_height(slope_col: Rect<i32>, slope: Slope, x: i32) -> i32 {
+    // How far above the slopes bottom edge the surface is at x, scaled up by the slopes width.
+    let across = match slope {
+        Slope::RISING => x - slope_col.position.x,
+        Slope::FALLING => slope_col.position.x + slope_col.size.x - x,
+    };
+    return across * slope_col.size.y;
+}
+
+fn get_highest_x(col: Rect<i32>, slope_col: Rect<i32>, slope: Slope) -> i32 {
+    // The x inside both colliders where the slopes surface is highest.
+    match slope {
+        Slope::RISING => return (col.position.x + col.size.x).min(slope_col.position.x + slope_col.size.x),
+        Slope::FALLING => return col.position.x.max(slope_col.position.x),
+    }
+}
+
+fn rect_overlaps_slope(col: Rect<i32>, slope_col: Rect<i32>, slope: Slope) -> bool {
+    // The rect overlaps if its bottom edge is under the surface where the surface is highest.
+    let slope_bottom = slope_col.position.y + slope_col.size.y;
+    let below_bottom = (slope_bottom - (col.position.y + col.size.y)) * slope_col.size.x;
+    return below_bottom < get_slope_height(slope_col, slope, get_highest_x(col, slope_col, slope));
+}
+
+fn circle_overlaps_slope(circle: Rect<i32>, slope_col: Rect<i32>, slope: Slope) -> bool {
+    // Checks how far the circles center is from the slopes surface, the bounds are already known to overlap.
+    let center = get_doubled_center(circle);
+    let slope_bottom = slope_col.position.y + slope_col.size.y;
+    let under_surface = get_slope_height(Rect { position: slope_col.position * 2, size: slope_col.size * 2 }, slope, center.x) / 2
+        - (slope_bottom * 2 - center.y) * slope_col.size.x;
+    if under_surface > 0 {
+        return true;
+    }
+    let surface_length_squared = slope_col.size.x * slope_col.size.x + slope_col.size.y * slope_col.size.y;
+    return under_surface * under_surface < circle.size.x * circle.size.x * surface_length_squared;
+}
+
+pub fn get_slope_lift(col: Rect<i32>, slope_col: Rect<i32>, slope: Slope) -> i32 {
+    // How far up col needs to move to stand on the slopes surface, or 0 if it is not in the slope.
+    if col.overlapping_rect(slope_col).is_none() || !rect_overlaps_slope(col, slope_col, slope) {
+        return 0;
+    }
+    let height = get_slope_height(slope_col, slope, get_highest_x(col, slope_col, slope));
+    let width = slope_col.size.x.max(1);
+    let surface = slope_col.position.y + slope_col.size.y - (height + width - 1) / width;
+    return col.position.y + col.size.y - surface;
+}
+
+pub fn is_riding(rider: Rect<i32>, platform: Rect<i32>) -> bool {
+    // If the riders bottom edge is resting on top of the platform.
+    let on_top = rider.position.y + rider.size.y == platform.position.y;
+    let overlapping_x = rider.position.x < platform.position.x + platform.size.x
+        && platform.position.x < rider.position.x + rider.size.x;
+    return on_top && overlapping_x;
+}
+
+pub fn get_push_out(obj: Rect<i32>, solid: Rect<i32>, motion: Vector2D<i32>) -> Vector2D<i32> {
+    // How far obj needs to move in the direction the solid is moving to stop overlapping it.
+    // Only pushes along the axis needing the smallest move, so a diagonal mover does not fling things sideways.
+    let no_push = Vector2D { x: 0, y: 0 };
+    if obj.overlapping_rect(solid).is_none() {
+        return no_push;
+    }
+    let push_x = if motion.x > 0 {
+        solid.position.x + solid.size.x - obj.position.x
+    } else if motion.x < 0 {
+        solid.position.x - (obj.position.x + obj.size.x)
+    } else {
+        0
+    };
+    let push_y = if motion.y > 0 {
+        solid.position.y + solid.size.y - obj.position.y
+    } else if motion.y < 0 {
+        solid.position.y - (obj.position.y + obj.size.y)
+    } else {
+        0
+    };
+    if push_x != 0 && (push_y == 0 || push_x.abs() <= push_y.abs()) {
+        return Vector2D { x: push_x, y: 0 };
+    }
+    return Vector2D { x: 0, y: push_y };
+}
+
+pub fn sweep(start: Rect<i32>, motion: Vector2D<i32>, walls: &[Rect<i32>], one_way_walls: &[Rect<i32>]) -> Vector2D<i32> {
+    // Moves a collider along x then y, stopping against the first wall in the way on each axis so it slides along them.
+    // Walls are checked along the whole path, so fast movers can not skip through thin walls.
+    // Walls the collider already overlaps are ignored, so something stuck inside one can still get out.
+    // One way walls only stop things moving down onto them, so they can be passed through from below or the sides.
+    let mut moved = start;
+    moved.position.x += move_axis(moved, motion.x, walls, true);
+    let mut distance_y = move_axis(moved, motion.y, walls, false);
+    if distance_y > 0 {
+        distance_y = move_axis(moved, distance_y, one_way_walls, false);
+    }
+    moved.position.y += distance_y;
+    return moved.position;
+}
+
+pub fn climb_slopes(start: Rect<i32>, moved: Vector2D<i32>, slopes: &[(Rect<i32>, Slope)]) -> Vector2D<i32> {
+    // Lifts a collider up onto any slopes it moved into, as long as the climb is no more than it moved.
+    // Anything steeper, like walking into the tall side of a slope, stops the sideways movement instead.
+    let motion = moved - start.position;
+    let max_climb = motion.x.abs() + motion.y.max(0);
+    let mut resolved = Rect { position: moved, size: start.size };
+    if slopes.iter().any(|(slope_col, slope)| get_slope_lift(resolved, *slope_col, *slope) > max_climb) {
+        resolved.position.x = start.position.x;
+    }
+    // Lifts are worked out from where it ended up, so a collider that started the frame slightly inside a slope is not thrown on top of it.
+    for (slope_col, slope) in slopes {
+        let lift = get_slope_lift(resolved, *slope_col, *slope);
+        if lift > 0 && lift <= max_climb {
+            resolved.position.y -= lift;
+        }
+    }
+    return resolved.position;
+}
+
+fn move_axis(col: Rect<i32>, distance: i32, walls: &[Rect<i32>], horizontal: bool) -> i32 {
+    let (pos, size, cross_pos, cross_size) = get_axes(col, horizontal);
+    let mut allowed = distance;
+    for wall in walls {
+        let (wall_pos, wall_size, wall_cross_pos, wall_cross_size) = get_axes(*wall, horizontal);
+        let in_path = cross_pos < wall_cross_pos + wall_cross_size && wall_cross_pos < cross_pos + cross_size;
+        if !in_path {
+            continue;
+        }
+        if distance > 0 && wall_pos >= pos + size {
+            allowed = allowed.min(wall_pos - (pos + size));
+        } else if distance < 0 && wall_pos + wall_size <= pos {
+            allowed = allowed.max(wall_pos + wall_size - pos);
+        }
+    }
+    return allowed;
+}
+
+fn get_axes(col: Rect<i32>, horizontal: bool) -> (i32, i32, i32, i32) {
+    // The position and size along the axis being moved on, then along the other axis.
+    if horizontal {
+        return (col.position.x, col.size.x, col.position.y, col.size.y);
+    }
+    return (col.position.y, col.size.y, col.position.x, col.size.x);
+}
@@ -6,17 +6,53 @@
     implementaiton works.
 */
 
-use agb::{display::GraphicsFrame, fixnum::{Rect, Vector2D}};
+use agb::{display::{GraphicsFrame, Priority}, fixnum::{Rect, Vector2D}};
 use alloc::boxed::Box;
+use crate::collision::{ColliderShape, HitBox};
 use crate::global_data;
 
+#[derive(Clone, Copy, PartialEq)]
 pub(crate) enum ResponseType {
     NONE,
     DAMAGE,
+    ATTACK,
+    PICKUP,
     WALL,
     PLAYER
 }
 
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Facing {
+    UP,
+    DOWN,
+    LEFT,
+    RIGHT
+}
+
+impl Facing {
+    pub fn get_vector(&self) -> Vector2D<i32> { //Gets a unit vector pointing in the facing direction.
+        match self {
+            Facing::UP => return Vector2D { x: 0, y: -1 },
+            Facing::DOWN => return Vector2D { x: 0, y: 1 },
+            Facing::LEFT => return Vector2D { x: -1, y: 0 },
+            Facing::RIGHT => return Vector2D { x: 1, y: 0 },
+        }
+    }
+
+    pub fn from_vector(vector: Vector2D<i32>) -> Facing { //Gets the facing closest to a vector, horizontal wins ties.
+        if vector.x.abs() >= vector.y.abs() {
+            if vector.x < 0 {
+                return Facing::LEFT;
+            }
+            return Facing::RIGHT;
+        }
+        if vector.y < 0 {
+            return Facing::UP;
+        }
+        return Facing::DOWN;
+    }
+}
+
 pub trait GameObj {
     fn ready(&mut self) { //Called after entering the object_box
         return;
@@ -56,10 +92,42 @@ pub trait GameObj {
         return ResponseType::NONE;
     }
 
+    fn check_collision_with_id(&mut self, other: &Box<dyn GameObj>, other_id: u32) -> ResponseType { //Same as "check_collision()", with the id the other object keeps while it exists, for objects that need to tell others apart between frames.
+        return self.check_collision(other);
+    }
+
     fn check_response_type(&self) -> ResponseType { //Returns the response type the object uses.
         return ResponseType::NONE;
     }
 
+    fn is_sensor(&self) -> bool { //Sensors never block or get blocked, and skip check_collision() for the overlap callbacks below.
+        return false;
+    }
+
+    fn on_overlap_begin(&mut self, other: &Box<dyn GameObj>) { //Called on a sensor the first frame another object overlaps it.
+        return;
+    }
+
+    fn on_overlap_stay(&mut self, other: &Box<dyn GameObj>) { //Called on a sensor each following frame the other object still overlaps it.
+        return;
+    }
+
+    fn on_overlap_end(&mut self, other: Option<&Box<dyn GameObj>>) { //Called on a sensor the frame the other object stops overlapping it, with None if the other was freed.
+        return;
+    }
+
+    fn get_hitboxes(&self) -> &[HitBox] { //Gets the named hitboxes and hurtboxes used for combat, placed relative to get_pos(). These never block anything.
+        return &[];
+    }
+
+    fn on_hurt(&mut self, hurtbox: &HitBox, hitbox: &HitBox, attacker: &Box<dyn GameObj>) { //Called when one of this objects hurtboxes is touched by another objects hitbox.
+        return;
+    }
+
+    fn on_hit(&mut self, hitbox: &HitBox, hurtbox: &HitBox, target: &Box<dyn GameObj>) { //Called when one of this objects hitboxes touches another objects hurtbox.
+        return;
+    }
+
     fn handle_response(&mut self, response: ResponseType) { //Handles the reaction from the object being checked in "check_collision()"
         return;
     }
@@ -68,10 +136,55 @@ pub trait GameObj {
         return None;
     }
 
+    fn get_collider_shape(&self) -> ColliderShape { //Gets which part of the collider is solid, defaults to all of it.
+        return ColliderShape::RECT;
+    }
+
     fn get_pos(&self) -> Option<Vector2D<i32>> {  //Gets the position of the object.
         return None;
     }
 
+    fn get_prev_pos(&self) -> Option<Vector2D<i32>> { //Gets where the object was at the start of the frame, objects returning this are stopped by walls.
+        return None;
+    }
+
+    fn set_resolved_pos(&mut self, pos: Vector2D<i32>) { //Moves the object to where it was stopped by a wall.
+        return;
+    }
+
+    fn get_motion(&self) -> Option<Vector2D<i32>> { //Gets how far a moving solid moved this frame, used to carry and push other objects.
+        return None;
+    }
+
+    fn stop_motion(&mut self) { //Undoes this frames movement, called when a moving solid would push something into a wall.
+        return;
+    }
+
+    fn is_pushable(&self) -> bool { //If moving solids can push and carry this object.
+        return false;
+    }
+
+    fn push(&mut self, offset: Vector2D<i32>) { //Moves the object when pushed or carried by a moving solid.
+        return;
+    }
+
+    fn get_facing(&self) -> Option<Facing> { //Gets the direction the object is facing, or None if it has no facing.
+        return None;
+    }
+
+    fn get_draw_layer(&self) -> i32 { //Gets the layer the object is drawn on, higher layers are drawn over lower ones.
+        return 0;
+    }
+
+    fn y_sort(&self) -> bool { //If true, objects lower on the screen are drawn over others on the same layer, useful for top-down games.
+        return false;
+    }
+
+    fn get_priority(&self) -> Priority { //Gets the priority of the object relative to the backgrounds, P0 draws over every background.
+        // Objects should apply this to their sprite with set_priority(), usually in ready().
+        return Priority::P0;
+    }
+
     fn draw(&self, frame: &mut GraphicsFrame) { //Draws the object
         return;
     }
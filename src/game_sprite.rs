@@ -0,0 +1,190 @@
+#![allow(dead_code, unused_variables)]
+
+/*
+    A sprite wrapper for actors, draws as a regular Object normally but
+    switches to a affine ObjectAffine when rotated or scaled.
+    The GBA only has 32 affine matrices per frame, so each transformed sprite
+    requests a slot from GlobalData in update_affine(), if none are left it
+    falls back to drawing untransformed for that frame.
+*/
+
+use agb::display::object::{AffineMatrixObject, AffineMode, Object, ObjectAffine, Sprite};
+use agb::display::{AffineMatrix, GraphicsFrame, Priority};
+use agb::fixnum::{Num, Vector2D, num};
+use crate::global_data;
+
+pub(crate) struct GameSprite {
+    object: Object,
+    affine_object: Option<ObjectAffine>,
+    sprite: &'static Sprite,
+    pos: Vector2D<i32>,
+    priority: Priority,
+    hflip: bool,
+    vflip: bool,
+    rotation: Num<i32, 8>,
+    scale: Vector2D<Num<i32, 8>>,
+    double_size: bool,
+    matrix_changed: bool,
+    use_affine: bool
+}
+
+impl GameSprite {
+    pub fn new(sprite: &'static Sprite) -> GameSprite {
+        GameSprite {
+            object: Object::new(sprite),
+            affine_object: None,
+            sprite,
+            pos: Vector2D { x: 0, y: 0 },
+            priority: Priority::P0,
+            hflip: false,
+            vflip: false,
+            rotation: num!(0),
+            scale: Vector2D { x: num!(1), y: num!(1) },
+            double_size: false,
+            matrix_changed: true,
+            use_affine: false
+        }
+    }
+
+    pub fn set_sprite(&mut self, sprite: &'static Sprite) {
+        if core::ptr::eq(self.sprite, sprite) {
+            return;
+        }
+        self.sprite = sprite;
+        self.object.set_sprite(sprite);
+        if let Some(affine_object) = &mut self.affine_object {
+            affine_object.set_sprite(sprite);
+        }
+    }
+
+    pub fn set_pos(&mut self, pos: Vector2D<i32>) {
+        self.pos = pos;
+        self.object.set_pos(pos);
+        let affine_pos = self.get_affine_pos();
+        if let Some(affine_object) = &mut self.affine_object {
+            affine_object.set_pos(affine_pos);
+        }
+    }
+
+    pub fn set_priority(&mut self, priority: Priority) {
+        self.priority = priority;
+        self.object.set_priority(priority);
+        if let Some(affine_object) = &mut self.affine_object {
+            affine_object.set_priority(priority);
+        }
+    }
+
+    pub fn set_hflip(&mut self, flip: bool) {
+        if self.hflip != flip {
+            self.hflip = flip;
+            self.matrix_changed = true;
+        }
+        self.object.set_hflip(flip);
+    }
+
+    pub fn set_vflip(&mut self, flip: bool) {
+        if self.vflip != flip {
+            self.vflip = flip;
+            self.matrix_changed = true;
+        }
+        self.object.set_vflip(flip);
+    }
+
+    pub fn set_rotation(&mut self, rotation: Num<i32, 8>) {
+        // Rotation is in turns, so 0.25 is a quarter turn.
+        if self.rotation != rotation {
+            self.rotation = rotation;
+            self.matrix_changed = true;
+        }
+    }
+
+    pub fn set_scale(&mut self, scale: Vector2D<Num<i32, 8>>) {
+        if self.scale != scale {
+            self.scale = scale;
+            self.matrix_changed = true;
+        }
+    }
+
+    pub fn set_double_size(&mut self, double_size: bool) {
+        // Doubles the drawn area so rotated or scaled up sprites are not clipped, the sprite stays centered on its position.
+        if self.double_size != double_size {
+            self.double_size = double_size;
+            self.matrix_changed = true;
+        }
+    }
+
+    pub fn reset_transform(&mut self) {
+        self.set_rotation(num!(0));
+        self.set_scale(Vector2D { x: num!(1), y: num!(1) });
+    }
+
+    pub fn is_transformed(&self) -> bool {
+        return self.rotation != num!(0) || self.scale != Vector2D { x: num!(1), y: num!(1) };
+    }
+
+    pub fn is_affine(&self) -> bool {
+        // If the sprite got a affine slot this frame.
+        return self.use_affine;
+    }
+
+    pub fn update_affine(&mut self, globals: &mut global_data::GlobalData) {
+        // Should be called once per frame in update() after the transform has been set.
+        self.use_affine = self.is_transformed() && globals.request_affine_slot();
+        if !self.use_affine {
+            return;
+        }
+        if self.affine_object.is_none() || self.matrix_changed {
+            let mut affine_object = ObjectAffine::new(self.sprite, self.get_matrix(), self.get_affine_mode());
+            affine_object.set_priority(self.priority);
+            affine_object.set_pos(self.get_affine_pos());
+            self.affine_object = Some(affine_object);
+            self.matrix_changed = false;
+        }
+    }
+
+    pub fn show(&self, frame: &mut GraphicsFrame) {
+        match &self.affine_object {
+            Some(affine_object) if self.use_affine => affine_object.show(frame),
+            _ => self.object.show(frame),
+        }
+    }
+
+    fn get_affine_mode(&self) -> AffineMode {
+        if self.double_size {
+            return AffineMode::AffineDouble;
+        }
+        return AffineMode::Affine;
+    }
+
+    fn get_affine_pos(&self) -> Vector2D<i32> {
+        if self.double_size {
+            let (width, height) = self.sprite.size().to_width_height();
+            return self.pos - Vector2D { x: width as i32 / 2, y: height as i32 / 2 };
+        }
+        return self.pos;
+    }
+
+    fn get_matrix(&self) -> AffineMatrixObject {
+        // The GBA matrix maps screen pixels to sprite pixels, so the scale is inverted. Flips are folded in as negative scales.
+        let mut inverse_scale = Vector2D {
+            x: num!(1) / non_zero(self.scale.x),
+            y: num!(1) / non_zero(self.scale.y),
+        };
+        if self.hflip {
+            inverse_scale.x = -inverse_scale.x;
+        }
+        if self.vflip {
+            inverse_scale.y = -inverse_scale.y;
+        }
+        let matrix = AffineMatrix::from_rotation(self.rotation) * AffineMatrix::from_scale(inverse_scale);
+        return AffineMatrixObject::new(matrix);
+    }
+}
+
+fn non_zero(value: Num<i32, 8>) -> Num<i32, 8> {
+    // Prevents dividing by zero when a sprite is scaled down to nothing.
+    if value == num!(0) {
+        return Num::from_raw(1);
+    }
+    return value;
+}
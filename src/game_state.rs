@@ -5,22 +5,35 @@
     Scene changes are handled in the globals struct.
 */
 
-use agb::{display::GraphicsFrame, println};
+use agb::{display::GraphicsFrame, fixnum::Rect, println, save::SaveData, sound::mixer::Mixer};
 use alloc::{boxed::Box, vec::Vec};
-use crate::{actor, game_obj::GameObj, global_data, scene};
+use core::cmp::Reverse;
+use crate::{actor, collision::{self, BoxType, ColliderShape, HitBox, Slope}, game_obj::{GameObj, ResponseType}, global_data, music, scene, sfx};
 
 pub(crate) struct GameState {
     obj_box: Vec<Box<dyn GameObj>>,
+    obj_ids: Vec<u32>, // Matches each object in the obj_box to a id that stays the same while it exists.
+    next_obj_id: u32,
+    sensor_overlaps: Vec<(u32, u32)>, // The sensor and other object ids that overlapped last frame.
+    hitbox_hits: Vec<(u32, &'static str, u32)>, // The attacker id, hitbox name and target id of each hit made by a hitbox that is still out.
     current_map: scene::SCENES,
     globals: global_data::GlobalData,
+    sfx_player: sfx::SfxPlayer,
+    music_player: music::MusicPlayer,
 }
 
 impl GameState {
     pub fn new() -> GameState {
         return GameState {
             obj_box: Vec::new(),
+            obj_ids: Vec::new(),
+            next_obj_id: 0,
+            sensor_overlaps: Vec::new(),
+            hitbox_hits: Vec::new(),
             current_map: scene::SCENES::TestScene,
-            globals: global_data::GlobalData::new()
+            globals: global_data::GlobalData::new(),
+            sfx_player: sfx::SfxPlayer::new(),
+            music_player: music::MusicPlayer::new()
         }
     }
 
@@ -30,6 +43,7 @@ impl GameState {
                 // Logic for when changing scenes.
                 self.change_scene(new_scene);
                 self.globals.reset_offset();
+                self.globals.set_player_pos(None);
                 self.globals.process_bg(frame);
                 self.globals.set_mode(global_data::GAMEMODE::PLAY);
             },
@@ -39,11 +53,18 @@ impl GameState {
                     global_data::GAMEMODE::PLAY => {
                         // Main gameplay loop logic.
                         self.globals.process_bg(frame);
-                        update_free(&mut self.obj_box);
+                        self.globals.reset_affine_slots();
+                        update_free(&mut self.obj_box, &mut self.obj_ids);
                         self.spawn_objs_in_queue();
                         update_objs(&mut self.obj_box, &mut self.globals);
-                        update_collisions(&mut self.obj_box);
-                        draw_objs(&mut self.obj_box, frame);
+                        update_platforms(&mut self.obj_box);
+                        resolve_movement(&mut self.obj_box);
+                        update_collisions(&mut self.obj_box, &self.obj_ids);
+                        update_sensors(&mut self.obj_box, &self.obj_ids, &mut self.sensor_overlaps);
+                        update_hitboxes(&mut self.obj_box, &self.obj_ids, &mut self.hitbox_hits);
+                        self.globals.update_world(&self.obj_box);
+                        self.globals.step_paths();
+                        draw_objs(&mut self.obj_box, &self.obj_ids, frame);
                         //_get_heap(&self.obj_box);
                         self.globals.reset_offset();
                     },
@@ -56,10 +77,33 @@ impl GameState {
         }
     }
 
+    pub fn load_save(&mut self, media: Option<SaveData>) {
+        // Hands the save media to the globals and loads the save file from it if there is one.
+        self.globals.set_save_media(media);
+        match self.globals.load_game() {
+            Ok(_) => {},
+            Err(err_msg) => println!("{}", err_msg),
+        }
+    }
+
+    pub fn process_sfx(&mut self, mixer: &mut Mixer<'_>) {
+        // Plays the sound effects objects queued this cycle, should be called before mixer.frame().
+        self.sfx_player.process_queue(mixer, self.globals.take_sfx_queue());
+    }
+
+    pub fn process_music(&mut self, mixer: &mut Mixer<'_>) {
+        // Runs the music commands objects queued this cycle then steps the BGM, should be called before mixer.frame().
+        for command in self.globals.take_music_queue() {
+            self.music_player.handle_command(mixer, command);
+        }
+        self.music_player.step(mixer);
+    }
+
     pub fn change_scene(&mut self, next_scene: scene::SCENES) {
         self.empty_box();
         let new_box = scene::get_layout(next_scene);
         self.globals.queue_bg_change(scene::get_bg_val(next_scene));
+        self.music_player.queue_track(scene::get_track(next_scene), scene::get_music_transition(next_scene));
         for obj in new_box {
             match self.add_obj(actor::spawn_actor(obj)) {
                 Ok(_) => {},
@@ -67,11 +111,16 @@ impl GameState {
             }
         }
         self.current_map = next_scene;
+        self.globals.set_current_scene(next_scene);
+        self.globals.reset_world();
+        self.globals.update_world(&self.obj_box);
     }
 
     pub fn add_obj(&mut self, new_obj: Box<dyn GameObj>) -> Result<bool, &str> {
-        if find_obj_slot(&mut self.obj_box) {
+        if find_obj_slot(&mut self.obj_box, &mut self.obj_ids) {
             self.obj_box.push(new_obj);
+            self.obj_ids.push(self.next_obj_id);
+            self.next_obj_id = self.next_obj_id.wrapping_add(1);
             match self.obj_box.last_mut() {
                 Some(val) => {
                     val.ready();
@@ -85,6 +134,11 @@ impl GameState {
     
     pub fn empty_box(&mut self) {
         self.obj_box.clear();
+        self.obj_ids.clear();
+        // Ids only need to be unique inside a scene, so each scene numbers its objects the same way every time it loads.
+        self.next_obj_id = 0;
+        self.sensor_overlaps.clear();
+        self.hitbox_hits.clear();
         assert!(self.obj_box.is_empty());
     }
 
@@ -99,6 +153,26 @@ impl GameState {
     }
 }
 
+#[cfg(test)]
+impl GameState {
+    // Lets the game loop tests look inside the state between frames.
+    pub fn get_objs(&self) -> &[Box<dyn GameObj>] {
+        return &self.obj_box;
+    }
+
+    pub fn get_current_scene(&self) -> scene::SCENES {
+        return self.current_map;
+    }
+
+    pub fn get_globals(&self) -> &global_data::GlobalData {
+        return &self.globals;
+    }
+
+    pub fn get_globals_mut(&mut self) -> &mut global_data::GlobalData {
+        return &mut self.globals;
+    }
+}
+
 fn update_objs(obj_box: &mut Vec<Box<dyn GameObj>>, globals: &mut global_data::GlobalData) {
     for obj in obj_box {
         if obj.on_screen() {
@@ -109,15 +183,29 @@ fn update_objs(obj_box: &mut Vec<Box<dyn GameObj>>, globals: &mut global_data::G
     }
 }
 
-fn draw_objs(obj_box: &mut Vec<Box<dyn GameObj>>, frame:&mut GraphicsFrame) {
-    for obj in obj_box {
-        if obj.on_screen() {
-            obj.draw(frame);
-        }
+fn draw_objs(obj_box: &mut [Box<dyn GameObj>], obj_ids: &[u32], frame:&mut GraphicsFrame) {
+    // Sorts the on screen objects from back to front, then draws them front to back.
+    // The GBA draws objects shown earlier in the frame over ones shown later.
+    let mut draw_order: Vec<usize> = (0..obj_box.len())
+        .filter(|index| obj_box[*index].on_screen())
+        .collect();
+    draw_order.sort_by_key(|index| get_draw_key(obj_box[*index].as_ref(), obj_ids[*index]));
+    for index in draw_order.into_iter().rev() {
+        obj_box[index].draw(frame);
     }
 }
 
-fn update_free(obj_box: &mut Vec<Box<dyn GameObj>>) {
+fn get_draw_key(obj: &dyn GameObj, id: u32) -> (Reverse<u8>, i32, i32, u32) {
+    // Background priority first, then the layer, then the Y position if the object is Y sorted.
+    // Ties go to the id, so objects that were added later are drawn on top no matter where they sit in the obj_box.
+    let sort_y = match obj.get_pos() {
+        Some(pos) if obj.y_sort() => pos.y,
+        _ => 0,
+    };
+    return (Reverse(obj.get_priority() as u8), obj.get_draw_layer(), sort_y, id);
+}
+
+fn update_free(obj_box: &mut Vec<Box<dyn GameObj>>, obj_ids: &mut Vec<u32>) {
     //Checks for objects queued for removal one at a time, then removes them, cycles until it reaches the end of the object box.
     let mut found_free: bool = false;
     let mut iter_count: usize = 0;
@@ -131,6 +219,7 @@ fn update_free(obj_box: &mut Vec<Box<dyn GameObj>>) {
         }
         if found_free == true {
             obj_box.swap_remove(iter_count);
+            obj_ids.swap_remove(iter_count);
             found_free = false;
             iter_count = 0;
             continue;
@@ -139,7 +228,7 @@ fn update_free(obj_box: &mut Vec<Box<dyn GameObj>>) {
     }
 }
 
-fn find_obj_slot(obj_box: &mut Vec<Box<dyn GameObj>>) -> bool {
+fn find_obj_slot(obj_box: &mut Vec<Box<dyn GameObj>>, obj_ids: &mut Vec<u32>) -> bool {
     if obj_box.len() < 128 {
         // If has enough slots to spawn something, returns true to allow new object to be spawned
         return true;
@@ -159,14 +248,214 @@ fn find_obj_slot(obj_box: &mut Vec<Box<dyn GameObj>>) -> bool {
         if can_spawn {
             // If a object that is cleanup able is found, it is removed from the obj_box and its slot will be filled with the new one later.
             obj_box.remove(iter_count);
+            obj_ids.remove(iter_count);
         }
         return can_spawn;
     }
 }
 
-fn update_collisions(obj_box: &mut Vec<Box<dyn GameObj>>) {
+fn update_platforms(obj_box: &mut [Box<dyn GameObj>]) {
+    // Carries objects riding on moving solids and pushes objects out of their way, before the other collisions are checked.
+    // If a push would shove something into a wall the solid is stopped instead, so nothing gets crushed.
+    for platform_index in 0..obj_box.len() {
+        let (Some(motion), Some(platform_col)) = (obj_box[platform_index].get_motion(), obj_box[platform_index].get_collider()) else {
+            continue;
+        };
+        if obj_box[platform_index].is_sensor() {
+            continue;
+        }
+        if motion.x == 0 && motion.y == 0 {
+            continue;
+        }
+        let prev_col = Rect { position: platform_col.position - motion, size: platform_col.size };
+        let mut pushes: Vec<(usize, agb::fixnum::Vector2D<i32>)> = Vec::new();
+        let mut blocked = false;
+        for (index, obj) in obj_box.iter().enumerate() {
+            if index == platform_index || !obj.is_pushable() || obj.is_sensor() {
+                continue;
+            }
+            let Some(col) = obj.get_collider() else {
+                continue;
+            };
+            let riding = collision::is_riding(col, prev_col);
+            let push = if riding { motion } else { collision::get_push_out(col, platform_col, motion) };
+            if push.x == 0 && push.y == 0 {
+                continue;
+            }
+            let pushed_col = Rect { position: col.position + push, size: col.size };
+            if hits_wall(obj_box, pushed_col, &[platform_index, index]) {
+                if riding {
+                    // Riders are left behind rather than carried into walls.
+                    continue;
+                }
+                blocked = true;
+                break;
+            }
+            pushes.push((index, push));
+        }
+        if blocked {
+            obj_box[platform_index].stop_motion();
+            continue;
+        }
+        for (index, push) in pushes {
+            obj_box[index].push(push);
+        }
+    }
+}
+
+fn resolve_movement(obj_box: &mut [Box<dyn GameObj>]) {
+    // Stops objects at any walls between where they started the frame and where they moved to, then lifts them up any slopes.
+    // Circles are blocked as their bounds, as the walls are stepped around one axis at a time.
+    let walls: Vec<(usize, Rect<i32>, ColliderShape)> = obj_box.iter().enumerate()
+        .filter(|(_, obj)| obj.check_response_type() == ResponseType::WALL && !obj.is_sensor())
+        .filter_map(|(index, obj)| Some((index, obj.get_collider()?, obj.get_collider_shape())))
+        .collect();
+    for (index, obj) in obj_box.iter_mut().enumerate() {
+        let (Some(prev_pos), Some(col)) = (obj.get_prev_pos(), obj.get_collider()) else {
+            continue;
+        };
+        let mut solid_walls: Vec<Rect<i32>> = Vec::new();
+        let mut one_way_walls: Vec<Rect<i32>> = Vec::new();
+        let mut slopes: Vec<(Rect<i32>, Slope)> = Vec::new();
+        for (wall_index, wall, shape) in walls.iter() {
+            if *wall_index == index {
+                continue;
+            }
+            match shape {
+                ColliderShape::ONEWAY => one_way_walls.push(*wall),
+                ColliderShape::SLOPE(slope) => slopes.push((*wall, *slope)),
+                _ => solid_walls.push(*wall),
+            }
+        }
+        let start = Rect { position: prev_pos, size: col.size };
+        let swept = collision::sweep(start, col.position - prev_pos, &solid_walls, &one_way_walls);
+        let resolved = collision::climb_slopes(start, swept, &slopes);
+        if resolved != col.position {
+            obj.set_resolved_pos(resolved);
+        }
+    }
+}
+
+fn update_sensors(obj_box: &mut [Box<dyn GameObj>], obj_ids: &[u32], sensor_overlaps: &mut Vec<(u32, u32)>) {
+    // Compares what each on screen sensor overlaps with last frame, to call its begin, stay and end callbacks.
+    // Sensors only track objects that are not sensors themselves.
+    let mut overlaps: Vec<(u32, u32)> = Vec::new();
+    for sensor_index in 0..obj_box.len() {
+        if !obj_box[sensor_index].is_sensor() || !obj_box[sensor_index].on_screen() {
+            continue;
+        }
+        for other_index in 0..obj_box.len() {
+            if other_index == sensor_index || obj_box[other_index].is_sensor() || !obj_box[other_index].on_screen() {
+                continue;
+            }
+            let (sensor, other) = get_pair_mut(obj_box, sensor_index, other_index);
+            if !shapes_overlap(sensor, other) {
+                continue;
+            }
+            let pair = (obj_ids[sensor_index], obj_ids[other_index]);
+            if sensor_overlaps.contains(&pair) {
+                sensor.on_overlap_stay(other);
+            } else {
+                sensor.on_overlap_begin(other);
+            }
+            overlaps.push(pair);
+        }
+    }
+    for (sensor_id, other_id) in sensor_overlaps.iter() {
+        if overlaps.contains(&(*sensor_id, *other_id)) {
+            continue;
+        }
+        // Sensors that were freed are not told, objects that were freed are passed on as None.
+        let Some(sensor_index) = obj_ids.iter().position(|id| id == sensor_id) else {
+            continue;
+        };
+        match obj_ids.iter().position(|id| id == other_id) {
+            Some(other_index) => {
+                let (sensor, other) = get_pair_mut(obj_box, sensor_index, other_index);
+                sensor.on_overlap_end(Some(other));
+            },
+            None => obj_box[sensor_index].on_overlap_end(None),
+        }
+    }
+    *sensor_overlaps = overlaps;
+}
+
+fn update_hitboxes(obj_box: &mut [Box<dyn GameObj>], obj_ids: &[u32], hitbox_hits: &mut Vec<(u32, &'static str, u32)>) {
+    // Finds every hitbox touching a hurtbox on another layer, then tells both objects about it.
+    // Each hitbox only hits a target once while it stays out, so a swing lasting several frames counts as one hit.
+    hitbox_hits.retain(|(attacker_id, name, _)| {
+        let Some(attacker_index) = obj_ids.iter().position(|id| id == attacker_id) else {
+            return false;
+        };
+        return obj_box[attacker_index].get_hitboxes().iter().any(|hitbox| hitbox.box_type == BoxType::HITBOX && hitbox.name == *name);
+    });
+    let mut hits: Vec<(usize, HitBox, usize, HitBox)> = Vec::new();
+    for (attacker_index, attacker) in obj_box.iter().enumerate() {
+        let Some(attacker_pos) = attacker.get_pos() else {
+            continue;
+        };
+        if !attacker.on_screen() {
+            continue;
+        }
+        for (target_index, target) in obj_box.iter().enumerate() {
+            let Some(target_pos) = target.get_pos() else {
+                continue;
+            };
+            if target_index == attacker_index || !target.on_screen() {
+                continue;
+            }
+            for hitbox in attacker.get_hitboxes() {
+                for hurtbox in target.get_hitboxes() {
+                    let hit = (obj_ids[attacker_index], hitbox.name, obj_ids[target_index]);
+                    if !hitbox_hits.contains(&hit) && hitbox.hits(attacker_pos, hurtbox, target_pos) {
+                        hitbox_hits.push(hit);
+                        hits.push((attacker_index, *hitbox, target_index, *hurtbox));
+                    }
+                }
+            }
+        }
+    }
+    for (attacker_index, hitbox, target_index, hurtbox) in hits {
+        let (attacker, target) = get_pair_mut(obj_box, attacker_index, target_index);
+        target.on_hurt(&hurtbox, &hitbox, attacker);
+        attacker.on_hit(&hitbox, &hurtbox, target);
+    }
+}
+
+fn get_pair_mut<T>(objs: &mut [T], first: usize, second: usize) -> (&mut T, &mut T) {
+    // Splits the list around the later of the pair, so both can be borrowed at once.
+    if first < second {
+        let (left, right) = objs.split_at_mut(second);
+        return (&mut left[first], &mut right[0]);
+    }
+    let (left, right) = objs.split_at_mut(first);
+    return (&mut right[0], &mut left[second]);
+}
+
+fn shapes_overlap(obj_1: &Box<dyn GameObj>, obj_2: &Box<dyn GameObj>) -> bool {
+    // Objects only check collisions against others their collider shapes are touching.
+    let (Some(col_1), Some(col_2)) = (obj_1.get_collider(), obj_2.get_collider()) else {
+        return false;
+    };
+    return collision::shapes_overlap(col_1, obj_1.get_collider_shape(), col_2, obj_2.get_collider_shape());
+}
+
+fn hits_wall(obj_box: &[Box<dyn GameObj>], col: Rect<i32>, ignore: &[usize]) -> bool {
+    for (index, obj) in obj_box.iter().enumerate() {
+        if ignore.contains(&index) || obj.check_response_type() != ResponseType::WALL || obj.is_sensor() {
+            continue;
+        }
+        if let Some(wall_col) = obj.get_collider() && wall_col.overlapping_rect(col).is_some() {
+            return true;
+        }
+    }
+    return false;
+}
+
+fn update_collisions(obj_box: &mut [Box<dyn GameObj>], obj_ids: &[u32]) {
     let mut active_objs: Vec<_> = obj_box.iter_mut()
-        .filter(|obj| obj.on_screen())
+        .zip(obj_ids.iter().copied())
+        .filter(|(obj, _)| obj.on_screen() && !obj.is_sensor())
         .collect();
     let len = active_objs.len();
     if len < 2 {
@@ -189,12 +478,14 @@ fn update_collisions(obj_box: &mut Vec<Box<dyn GameObj>>) {
                 In the above example, ideally the Player will treat all hits from any Enemy type the same.
                 Whereas the Enemy might handle the reply in a unique way. (Enemy might bounce? Or teleport? Maybe turn around? Kill itself?)
                 */
-                let (left, right) = active_objs.split_at_mut(next_block);
-                if left.len() == 0 {
-                    break;
+                let ((current, _), (next, next_id)) = get_pair_mut(&mut active_objs, current_block, next_block);
+                // Objects without a collider are still checked, so they can test the overlap themselves in check_collision().
+                let has_colliders = current.get_collider().is_some() && next.get_collider().is_some();
+                if has_colliders && !shapes_overlap(current, next) {
+                    continue;
                 }
-                let reply = left[current_block].check_collision(right[0]);
-                right[0].handle_response(reply);
+                let reply = current.check_collision_with_id(next, *next_id);
+                next.handle_response(reply);
             }
         }
     }
@@ -0,0 +1,653 @@
+/*
+    Headless tests for the game loop, ran under the agb test runner with "cargo test".
+    A TestHarness owns a GameState, feeds it scripted button presses through the input replay
+    and steps cycle_update() one frame at a time, so tests can check where objects ended up,
+    which flags are set and what scene is loaded.
+*/
+
+use agb::display::Graphics;
+use agb::fixnum::{Rect, Vector2D};
+use agb::include_aseprite;
+use agb::input::Button;
+use agb::Gba;
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use core::cell::Cell;
+use crate::actor::{self, Actor};
+use crate::actor::enemy::Behaviour;
+use crate::actor::gate::GateType;
+use crate::actor::pickup::PickupType;
+use crate::actor::platform::PlatformPath;
+use crate::actor::projectile::{Owner, ProjectileRequest};
+use crate::actor::terrain::TerrainType;
+use crate::actor::trigger::TriggerType;
+use crate::collision::{self, BoxType, ColliderShape, HitBox, HitLayer, Slope};
+use crate::game_obj::{GameObj, ResponseType};
+use crate::game_state::GameState;
+use crate::global_data::Flags;
+use crate::input_map::{Actions, InputMap};
+use crate::input_replay::InputLog;
+use crate::inventory::Items;
+use crate::meta_sprite::MetaSprite;
+use crate::pathfinding::PathState;
+use crate::scene::SCENES;
+
+include_aseprite!(
+    mod sprites,
+    "gfx/new_img.aseprite"
+);
+
+pub(crate) struct TestHarness<'gba> {
+    game_state: GameState,
+    gfx: Graphics<'gba>
+}
+
+impl<'gba> TestHarness<'gba> {
+    pub fn new(gba: &'gba mut Gba, scene: SCENES) -> TestHarness<'gba> {
+        let mut game_state = GameState::new();
+        game_state.change_scene(scene);
+        TestHarness {
+            game_state,
+            gfx: gba.graphics.get()
+        }
+    }
+
+    pub fn step(&mut self, frames: usize) {
+        // Runs the game loop without a mixer, so queued sounds are thrown away each frame.
+        for _ in 0..frames {
+            let mut frame = self.gfx.frame();
+            self.game_state.cycle_update(&mut frame);
+            let globals = self.game_state.get_globals_mut();
+            globals.take_sfx_queue();
+            globals.take_music_queue();
+            frame.commit();
+        }
+    }
+
+    pub fn hold(&mut self, buttons: Button, frames: usize) {
+        // Holds the buttons down for the given number of frames, then lets go of them.
+        let mut log = InputLog::new(self.game_state.get_current_scene(), InputMap::new());
+        for _ in 0..frames {
+            log.push(buttons.bits() as u16);
+        }
+        self.game_state.get_globals_mut().inject_input(log);
+        self.step(frames);
+    }
+
+    pub fn press(&mut self, buttons: Button) {
+        // Presses the buttons for a single frame.
+        self.hold(buttons, 1);
+    }
+
+    pub fn spawn(&mut self, actor_type: Actor) {
+        self.game_state.add_obj(actor::spawn_actor(actor_type)).expect("Unable to spawn test object");
+    }
+
+    pub fn get_positions(&self, response: ResponseType) -> alloc::vec::Vec<Vector2D<i32>> {
+        // Positions of every object using the given ResponseType, in obj_box order.
+        return self.game_state.get_objs().iter()
+            .filter(|obj| obj.check_response_type() == response)
+            .filter_map(|obj| obj.get_pos())
+            .collect();
+    }
+
+    pub fn get_player_pos(&self) -> Vector2D<i32> {
+        return *self.get_positions(ResponseType::PLAYER).first().expect("No player in the scene");
+    }
+
+    pub fn get_player_health(&self) -> u8 {
+        return self.game_state.get_globals().get_player_health();
+    }
+
+    pub fn get_scene(&self) -> SCENES {
+        return self.game_state.get_current_scene();
+    }
+
+    pub fn check_flag(&self, entry: Flags) -> bool {
+        return self.game_state.get_globals().check_flag(entry);
+    }
+}
+
+// A world hurtbox with no invincibility, counting every hit it takes. (example: a breakable pot)
+const COUNTER_BOXES: [HitBox; 1] = [HitBox::new("body", BoxType::HURTBOX, HitLayer::WORLD, Vector2D { x: 0, y: 0 }, Vector2D { x: 16, y: 16 })];
+
+struct HitCounter {
+    pos: Vector2D<i32>,
+    hits: Rc<Cell<u32>>
+}
+
+impl GameObj for HitCounter {
+    fn get_hitboxes(&self) -> &[HitBox] {
+        return &COUNTER_BOXES;
+    }
+
+    fn on_hurt(&mut self, _hurtbox: &HitBox, _hitbox: &HitBox, _attacker: &Box<dyn GameObj>) {
+        self.hits.set(self.hits.get() + 1);
+    }
+
+    fn get_pos(&self) -> Option<Vector2D<i32>> {
+        return Some(self.pos);
+    }
+}
+
+#[test_case]
+fn player_moves_with_input(gba: &mut Gba) {
+    let mut harness = TestHarness::new(gba, SCENES::Map001);
+    harness.hold(Button::RIGHT, 10);
+    assert_eq!(harness.get_player_pos(), Vector2D { x: 60, y: 50 });
+    harness.hold(Button::DOWN, 5);
+    assert_eq!(harness.get_player_pos(), Vector2D { x: 60, y: 55 });
+    harness.step(10);
+    assert_eq!(harness.get_player_pos(), Vector2D { x: 60, y: 55 });
+}
+
+#[test_case]
+fn player_is_stopped_by_walls(gba: &mut Gba) {
+    let mut harness = TestHarness::new(gba, SCENES::Map001);
+    harness.spawn(Actor::AWall(Vector2D { x: 70, y: 50 }));
+    harness.hold(Button::RIGHT, 30);
+    let player_pos = harness.get_player_pos();
+    assert_eq!(player_pos.x, 54, "Player did not stop flush against the wall");
+    assert_eq!(player_pos.y, 50);
+}
+
+#[test_case]
+fn player_slides_along_walls(gba: &mut Gba) {
+    // Moving diagonally into a floor keeps the movement along it.
+    let mut harness = TestHarness::new(gba, SCENES::Map001);
+    harness.spawn(Actor::AWall(Vector2D { x: 40, y: 66 }));
+    harness.spawn(Actor::AWall(Vector2D { x: 56, y: 66 }));
+    harness.hold(Button::RIGHT | Button::DOWN, 10);
+    assert_eq!(harness.get_player_pos(), Vector2D { x: 60, y: 50 });
+}
+
+#[test_case]
+fn fast_movers_do_not_pass_through_walls(_gba: &mut Gba) {
+    let start = Rect { position: Vector2D { x: 0, y: 0 }, size: Vector2D { x: 16, y: 16 } };
+    let walls = [Rect { position: Vector2D { x: 20, y: 8 }, size: Vector2D { x: 4, y: 16 } }];
+    let resolved = collision::sweep(start, Vector2D { x: 40, y: 10 }, &walls, &[]);
+    assert_eq!(resolved, Vector2D { x: 4, y: 10 });
+}
+
+#[test_case]
+fn collider_shapes_overlap(_gba: &mut Gba) {
+    let circle = Rect { position: Vector2D { x: 0, y: 0 }, size: Vector2D { x: 16, y: 16 } };
+    let corner = Rect { position: Vector2D { x: 14, y: 14 }, size: Vector2D { x: 4, y: 4 } };
+    let side = Rect { position: Vector2D { x: 12, y: 6 }, size: Vector2D { x: 4, y: 4 } };
+    assert!(!collision::shapes_overlap(circle, ColliderShape::CIRCLE, corner, ColliderShape::RECT), "Circle reached into the corner of its bounds");
+    assert!(collision::shapes_overlap(circle, ColliderShape::CIRCLE, side, ColliderShape::RECT));
+    let above = Rect { position: Vector2D { x: 0, y: 0 }, size: Vector2D { x: 4, y: 4 } };
+    let below = Rect { position: Vector2D { x: 12, y: 12 }, size: Vector2D { x: 4, y: 4 } };
+    let slope = ColliderShape::SLOPE(Slope::RISING);
+    assert!(!collision::shapes_overlap(above, ColliderShape::RECT, circle, slope), "Rect above the slope overlapped it");
+    assert!(collision::shapes_overlap(below, ColliderShape::RECT, circle, slope));
+}
+
+#[test_case]
+fn player_walks_up_ramps(gba: &mut Gba) {
+    let mut harness = TestHarness::new(gba, SCENES::Map001);
+    harness.spawn(Actor::ATerrain(Vector2D { x: 66, y: 50 }, TerrainType::RAMP(Slope::RISING)));
+    harness.hold(Button::RIGHT, 8);
+    assert_eq!(harness.get_player_pos(), Vector2D { x: 58, y: 42 });
+}
+
+#[test_case]
+fn ramps_block_from_their_tall_side(gba: &mut Gba) {
+    let mut harness = TestHarness::new(gba, SCENES::Map001);
+    harness.spawn(Actor::ATerrain(Vector2D { x: 66, y: 50 }, TerrainType::RAMP(Slope::FALLING)));
+    harness.hold(Button::RIGHT, 8);
+    assert_eq!(harness.get_player_pos(), Vector2D { x: 50, y: 50 });
+}
+
+#[test_case]
+fn ramps_block_from_their_tall_side_when_already_inside(gba: &mut Gba) {
+    let mut harness = TestHarness::new(gba, SCENES::Map001);
+    // Overlaps the tall side by 2 pixels from the start.
+    harness.spawn(Actor::ATerrain(Vector2D { x: 64, y: 50 }, TerrainType::RAMP(Slope::FALLING)));
+    harness.hold(Button::RIGHT, 8);
+    assert_eq!(harness.get_player_pos(), Vector2D { x: 50, y: 50 });
+}
+
+#[test_case]
+fn ledges_only_block_from_above(gba: &mut Gba) {
+    let mut harness = TestHarness::new(gba, SCENES::Map001);
+    harness.spawn(Actor::ATerrain(Vector2D { x: 50, y: 70 }, TerrainType::LEDGE(1)));
+    harness.spawn(Actor::ATerrain(Vector2D { x: 50, y: 30 }, TerrainType::LEDGE(1)));
+    harness.hold(Button::DOWN, 10);
+    assert_eq!(harness.get_player_pos().y, 54, "Player fell through the ledge below");
+    harness.hold(Button::UP, 30);
+    assert_eq!(harness.get_player_pos().y, 24, "Player was stopped by the ledge above");
+}
+
+#[test_case]
+fn camera_scrolls_objects(gba: &mut Gba) {
+    // Walking past the scroll edge keeps the player in place and moves everything else instead.
+    let mut harness = TestHarness::new(gba, SCENES::TestScene);
+    harness.hold(Button::LEFT, 60);
+    assert!(harness.get_player_pos().x >= 14);
+    let leftmost_wall = harness.get_positions(ResponseType::WALL).iter().map(|pos| pos.x).min().unwrap();
+    assert!(leftmost_wall > -120, "Walls did not scroll, leftmost wall at x {}", leftmost_wall);
+}
+
+#[test_case]
+fn cancel_changes_scene(gba: &mut Gba) {
+    let mut harness = TestHarness::new(gba, SCENES::TestScene);
+    harness.step(1);
+    assert!(harness.get_scene() == SCENES::TestScene);
+    harness.press(Button::SELECT);
+    harness.step(1);
+    assert!(harness.get_scene() == SCENES::Map001);
+    assert_eq!(harness.get_player_pos(), Vector2D { x: 50, y: 50 });
+}
+
+#[test_case]
+fn recording_resets_flags(gba: &mut Gba) {
+    let mut harness = TestHarness::new(gba, SCENES::TestScene);
+    harness.game_state.get_globals_mut().set_flag(Flags::TestWallSwitch, true);
+    assert!(harness.check_flag(Flags::TestWallSwitch));
+    harness.game_state.get_globals_mut().start_recording(SCENES::Map001);
+    harness.step(1);
+    assert!(!harness.check_flag(Flags::TestWallSwitch));
+    assert!(harness.get_scene() == SCENES::Map001);
+    assert_eq!(harness.game_state.get_globals_mut().stop_replay().get_frame_count(), 0);
+}
+
+#[test_case]
+fn replays_use_the_recorded_bindings(gba: &mut Gba) {
+    let mut harness = TestHarness::new(gba, SCENES::Map001);
+    harness.game_state.get_globals_mut().start_recording(SCENES::Map001);
+    harness.step(1);
+    let mut log = harness.game_state.get_globals_mut().stop_replay();
+    // Tests can't hold the real buttons, so the recorded input is added to the log afterwards.
+    for _ in 0..10 {
+        log.push(Button::RIGHT.bits() as u16);
+    }
+    let log = InputLog::from_bytes(&log.to_bytes()).expect("Unable to read the log back");
+    harness.game_state.get_globals_mut().rebind_action(Actions::MoveRight, Button::L);
+    harness.game_state.get_globals_mut().start_replay(log);
+    harness.step(11);
+    assert_eq!(harness.get_player_pos(), Vector2D { x: 60, y: 50 });
+    // The new binding is used again once the replay is over.
+    harness.hold(Button::L, 10);
+    assert_eq!(harness.get_player_pos(), Vector2D { x: 70, y: 50 });
+}
+
+#[test_case]
+fn enemy_patrols_between_points(gba: &mut Gba) {
+    let mut harness = TestHarness::new(gba, SCENES::Map001);
+    harness.spawn(Actor::AEnemy(Vector2D { x: 100, y: 100 }, Behaviour::PATROL(Vector2D { x: 20, y: 0 })));
+    harness.step(40);
+    assert_eq!(harness.get_positions(ResponseType::DAMAGE), [Vector2D { x: 120, y: 100 }]);
+    harness.step(40);
+    assert_eq!(harness.get_positions(ResponseType::DAMAGE), [Vector2D { x: 100, y: 100 }]);
+}
+
+#[test_case]
+fn enemy_is_defeated_by_attacks(gba: &mut Gba) {
+    let mut harness = TestHarness::new(gba, SCENES::Map001);
+    harness.spawn(Actor::AEnemy(Vector2D { x: 100, y: 100 }, Behaviour::IDLE));
+    for hit in 0..3 {
+        let enemy_pos = *harness.get_positions(ResponseType::DAMAGE).first().expect("Enemy was defeated early");
+        let shot_pos = enemy_pos + Vector2D { x: -12, y: 4 };
+        harness.spawn(Actor::AProjectile(ProjectileRequest::from_owner(shot_pos, Vector2D { x: 2, y: 0 }, Owner::PLAYER)));
+        harness.step(40);
+        assert!(harness.get_positions(ResponseType::ATTACK).is_empty(), "Attack {} did not hit", hit);
+    }
+    assert!(harness.get_positions(ResponseType::DAMAGE).is_empty());
+}
+
+#[test_case]
+fn player_is_hurt_and_knocked_back(gba: &mut Gba) {
+    let mut harness = TestHarness::new(gba, SCENES::Map001);
+    let max_health = harness.get_player_health();
+    harness.spawn(Actor::AEnemy(Vector2D { x: 64, y: 50 }, Behaviour::IDLE));
+    harness.step(12);
+    assert_eq!(harness.get_player_health(), max_health - 1);
+    assert_eq!(harness.get_player_pos(), Vector2D { x: 34, y: 50 });
+}
+
+#[test_case]
+fn player_dies_and_respawns(gba: &mut Gba) {
+    let mut harness = TestHarness::new(gba, SCENES::Map001);
+    let max_health = harness.get_player_health();
+    harness.game_state.get_globals_mut().damage_player(max_health - 1);
+    harness.spawn(Actor::AEnemy(Vector2D { x: 64, y: 50 }, Behaviour::IDLE));
+    harness.step(2);
+    assert_eq!(harness.get_player_health(), 0);
+    harness.hold(Button::RIGHT, 30);
+    assert_eq!(harness.get_player_pos().x, 50, "Player moved while dying");
+    harness.step(40);
+    assert_eq!(harness.get_player_health(), max_health);
+    assert_eq!(harness.get_player_pos(), Vector2D { x: 50, y: 50 });
+    assert!(harness.get_positions(ResponseType::DAMAGE).is_empty());
+}
+
+#[test_case]
+fn player_attacks_defeat_enemies(gba: &mut Gba) {
+    let mut harness = TestHarness::new(gba, SCENES::Map001);
+    harness.spawn(Actor::AEnemy(Vector2D { x: 100, y: 50 }, Behaviour::IDLE));
+    harness.press(Button::RIGHT);
+    for _ in 0..3 {
+        harness.press(Button::A);
+        harness.step(40);
+    }
+    assert!(harness.get_positions(ResponseType::DAMAGE).is_empty());
+    assert!(harness.get_positions(ResponseType::ATTACK).is_empty());
+}
+
+#[test_case]
+fn sword_swings_hit_enemies_once(gba: &mut Gba) {
+    // The wall stops the knockback, so the enemy stays in reach of every swing.
+    let mut harness = TestHarness::new(gba, SCENES::Map001);
+    harness.spawn(Actor::AEnemy(Vector2D { x: 70, y: 50 }, Behaviour::IDLE));
+    harness.spawn(Actor::AWall(Vector2D { x: 86, y: 50 }));
+    harness.press(Button::RIGHT);
+    harness.press(Button::R);
+    harness.step(40);
+    assert_eq!(harness.get_positions(ResponseType::DAMAGE).len(), 1, "A single swing hit more than once");
+    for _ in 0..2 {
+        harness.press(Button::R);
+        harness.step(40);
+    }
+    assert!(harness.get_positions(ResponseType::DAMAGE).is_empty());
+    assert_eq!(harness.get_player_pos(), Vector2D { x: 51, y: 50 }, "The sword blocked or moved the player");
+}
+
+#[test_case]
+fn sword_swings_hit_targets_without_invincibility_once(gba: &mut Gba) {
+    let mut harness = TestHarness::new(gba, SCENES::Map001);
+    let hits = Rc::new(Cell::new(0));
+    harness.game_state.add_obj(Box::new(HitCounter { pos: Vector2D { x: 70, y: 50 }, hits: hits.clone() })).expect("Unable to spawn test object");
+    harness.press(Button::RIGHT);
+    harness.press(Button::R);
+    harness.step(40);
+    assert_eq!(hits.get(), 1, "A single swing hit more than once");
+    harness.press(Button::R);
+    harness.step(40);
+    assert_eq!(hits.get(), 2, "A new swing did not hit again");
+}
+
+#[test_case]
+fn projectiles_pierce_targets(gba: &mut Gba) {
+    let mut harness = TestHarness::new(gba, SCENES::Map001);
+    harness.spawn(Actor::AEnemy(Vector2D { x: 100, y: 100 }, Behaviour::IDLE));
+    harness.spawn(Actor::AEnemy(Vector2D { x: 150, y: 100 }, Behaviour::IDLE));
+    let request = ProjectileRequest::new(Vector2D { x: 80, y: 104 }, Vector2D { x: 2, y: 0 }, Owner::PLAYER, 120, 1);
+    harness.spawn(Actor::AProjectile(request));
+    harness.step(60);
+    // Both enemies are knocked back by the one shot, which is used up by the second.
+    assert_eq!(harness.get_positions(ResponseType::DAMAGE), [Vector2D { x: 110, y: 100 }, Vector2D { x: 160, y: 100 }]);
+    assert!(harness.get_positions(ResponseType::ATTACK).is_empty());
+}
+
+#[test_case]
+fn projectiles_count_overlapping_targets(gba: &mut Gba) {
+    let mut harness = TestHarness::new(gba, SCENES::Map001);
+    harness.spawn(Actor::AEnemy(Vector2D { x: 100, y: 100 }, Behaviour::IDLE));
+    harness.spawn(Actor::AEnemy(Vector2D { x: 108, y: 100 }, Behaviour::IDLE));
+    let request = ProjectileRequest::new(Vector2D { x: 84, y: 104 }, Vector2D { x: 4, y: 0 }, Owner::PLAYER, 120, 1);
+    harness.spawn(Actor::AProjectile(request));
+    // Moves from the first enemy straight into the second without leaving either, which still uses up the shot.
+    harness.step(20);
+    assert!(harness.get_positions(ResponseType::ATTACK).is_empty());
+}
+
+#[test_case]
+fn player_shots_pass_through_enemy_shots(gba: &mut Gba) {
+    let mut harness = TestHarness::new(gba, SCENES::Map001);
+    harness.spawn(Actor::AProjectile(ProjectileRequest::new(Vector2D { x: 120, y: 104 }, Vector2D { x: 0, y: 0 }, Owner::ENEMY, 120, 0)));
+    harness.spawn(Actor::AProjectile(ProjectileRequest::new(Vector2D { x: 80, y: 104 }, Vector2D { x: 2, y: 0 }, Owner::PLAYER, 120, 0)));
+    // The player shot crosses the enemy shot around frame 20 and carries on past it.
+    harness.step(40);
+    assert_eq!(harness.get_positions(ResponseType::ATTACK).len(), 1);
+    assert_eq!(harness.get_positions(ResponseType::DAMAGE).len(), 1);
+}
+
+#[test_case]
+fn projectiles_expire(gba: &mut Gba) {
+    let mut harness = TestHarness::new(gba, SCENES::Map001);
+    let request = ProjectileRequest::new(Vector2D { x: 100, y: 100 }, Vector2D { x: 0, y: 0 }, Owner::ENEMY, 10, 0);
+    harness.spawn(Actor::AProjectile(request));
+    harness.step(9);
+    assert_eq!(harness.get_positions(ResponseType::DAMAGE).len(), 1);
+    harness.step(2);
+    assert!(harness.get_positions(ResponseType::DAMAGE).is_empty());
+}
+
+#[test_case]
+fn segment_casts_find_the_first_hit(gba: &mut Gba) {
+    let mut harness = TestHarness::new(gba, SCENES::Map001);
+    harness.spawn(Actor::AWall(Vector2D { x: 150, y: 50 }));
+    harness.spawn(Actor::AWall(Vector2D { x: 100, y: 50 }));
+    harness.step(1);
+    // Starts inside the player, so only the walls can be hit.
+    let globals = harness.game_state.get_globals();
+    let hit = globals.cast_segment(Vector2D { x: 58, y: 58 }, Vector2D { x: 200, y: 58 }, &[ResponseType::WALL, ResponseType::PLAYER]).expect("Cast missed both walls");
+    assert_eq!(hit.point, Vector2D { x: 100, y: 58 });
+    assert!(hit.response == ResponseType::WALL);
+    assert!(globals.cast_segment(Vector2D { x: 58, y: 58 }, Vector2D { x: 58, y: 150 }, &[ResponseType::WALL]).is_none());
+    assert!(!globals.has_line_of_sight(Vector2D { x: 58, y: 58 }, Vector2D { x: 200, y: 58 }));
+}
+
+#[test_case]
+fn diagonal_rays_go_the_given_distance(gba: &mut Gba) {
+    let mut harness = TestHarness::new(gba, SCENES::Map001);
+    harness.spawn(Actor::AWall(Vector2D { x: 100, y: 100 }));
+    harness.step(1);
+    // The wall corner is about 59 pixels away along the diagonal.
+    let globals = harness.game_state.get_globals();
+    assert!(globals.cast_ray(Vector2D { x: 58, y: 58 }, Vector2D { x: 1, y: 1 }, 50, &[ResponseType::WALL]).is_none());
+    let hit = globals.cast_ray(Vector2D { x: 58, y: 58 }, Vector2D { x: 1, y: 1 }, 70, &[ResponseType::WALL]).expect("Ray fell short of the wall");
+    assert_eq!(hit.point, Vector2D { x: 100, y: 100 });
+}
+
+#[test_case]
+fn snipers_hit_the_player_they_can_see(gba: &mut Gba) {
+    let mut harness = TestHarness::new(gba, SCENES::Map001);
+    harness.spawn(Actor::AEnemy(Vector2D { x: 150, y: 50 }, Behaviour::SNIPE(200, 30)));
+    harness.step(60);
+    assert!(harness.get_player_health() < 5, "Sniper never hit the player");
+}
+
+#[test_case]
+fn walls_block_enemy_sight(gba: &mut Gba) {
+    let mut harness = TestHarness::new(gba, SCENES::Map001);
+    harness.spawn(Actor::AEnemy(Vector2D { x: 150, y: 50 }, Behaviour::SNIPE(200, 30)));
+    harness.spawn(Actor::AWall(Vector2D { x: 100, y: 50 }));
+    harness.step(60);
+    assert_eq!(harness.get_player_health(), 5);
+}
+
+#[test_case]
+fn paths_route_around_walls(gba: &mut Gba) {
+    let mut harness = TestHarness::new(gba, SCENES::Map001);
+    for y in [48, 64, 80] {
+        harness.spawn(Actor::AWall(Vector2D { x: 96, y }));
+    }
+    harness.step(1);
+    let globals = harness.game_state.get_globals_mut();
+    let id = globals.find_path(Vector2D { x: 72, y: 72 }, Vector2D { x: 136, y: 72 }).expect("Search was not started");
+    assert!(matches!(globals.take_path(id), PathState::SEARCHING));
+    harness.step(5);
+    let PathState::FOUND(path) = harness.game_state.get_globals_mut().take_path(id) else {
+        panic!("No path was found");
+    };
+    // Four cells straight across, plus two up and two back down to get around the wall.
+    assert_eq!(path.len(), 9);
+    assert_eq!(path.first(), Some(&Vector2D { x: 64, y: 64 }));
+    assert_eq!(path.last(), Some(&Vector2D { x: 128, y: 64 }));
+    assert!(path.iter().all(|cell| cell.x != 96 || cell.y < 48 || cell.y > 80), "Path went through the wall");
+}
+
+#[test_case]
+fn path_searches_reuse_their_slots(gba: &mut Gba) {
+    let mut harness = TestHarness::new(gba, SCENES::Map001);
+    for _ in 0..3 {
+        let globals = harness.game_state.get_globals_mut();
+        let ids: alloc::vec::Vec<u16> = (0..4).map(|_| globals.find_path(Vector2D { x: 72, y: 72 }, Vector2D { x: 88, y: 72 }).expect("Search was not started")).collect();
+        harness.step(1);
+        let globals = harness.game_state.get_globals_mut();
+        for id in ids {
+            assert!(matches!(globals.take_path(id), PathState::FOUND(_)), "Search did not finish");
+        }
+    }
+    // Points outside the grid can not be searched for.
+    assert!(harness.game_state.get_globals_mut().find_path(Vector2D { x: 72, y: 72 }, Vector2D { x: 2000, y: 72 }).is_none());
+}
+
+#[test_case]
+fn enemies_hunt_around_walls(gba: &mut Gba) {
+    let mut harness = TestHarness::new(gba, SCENES::Map001);
+    harness.spawn(Actor::AEnemy(Vector2D { x: 144, y: 48 }, Behaviour::HUNT(200)));
+    for y in [32, 48, 64] {
+        harness.spawn(Actor::AWall(Vector2D { x: 112, y }));
+    }
+    harness.step(400);
+    assert!(harness.get_player_health() < 5, "Enemy never reached the player");
+}
+
+#[test_case]
+fn pickups_give_items(gba: &mut Gba) {
+    let mut harness = TestHarness::new(gba, SCENES::Map001);
+    harness.spawn(Actor::APickup(Vector2D { x: 70, y: 54 }, PickupType::ITEM(Items::Coin, 3)));
+    harness.hold(Button::RIGHT, 10);
+    assert_eq!(harness.game_state.get_globals().get_item_count(Items::Coin), 3);
+    assert!(harness.get_positions(ResponseType::PICKUP).is_empty());
+}
+
+#[test_case]
+fn health_pickups_only_heal_when_hurt(gba: &mut Gba) {
+    let mut harness = TestHarness::new(gba, SCENES::Map001);
+    let max_health = harness.get_player_health();
+    harness.spawn(Actor::APickup(Vector2D { x: 54, y: 54 }, PickupType::HEALTH(1)));
+    harness.step(3);
+    assert_eq!(harness.get_positions(ResponseType::PICKUP).len(), 1);
+    harness.game_state.get_globals_mut().damage_player(2);
+    harness.step(3);
+    assert_eq!(harness.get_player_health(), max_health - 1);
+    assert!(harness.get_positions(ResponseType::PICKUP).is_empty());
+}
+
+#[test_case]
+fn items_stack_up_to_their_limit(gba: &mut Gba) {
+    let mut harness = TestHarness::new(gba, SCENES::Map001);
+    let globals = harness.game_state.get_globals_mut();
+    assert_eq!(globals.give_item(Items::Key, 20), 9);
+    assert!(!globals.take_item(Items::Key, 10));
+    assert!(globals.take_item(Items::Key, 9));
+    assert!(!globals.has_item(Items::Key));
+}
+
+#[test_case]
+fn levers_toggle_doors(gba: &mut Gba) {
+    let mut harness = TestHarness::new(gba, SCENES::Map001);
+    harness.spawn(Actor::ATrigger(Vector2D { x: 100, y: 50 }, TriggerType::LEVER, Flags::TestWallSwitch));
+    harness.spawn(Actor::AGate(Vector2D { x: 50, y: 100 }, GateType::DOOR, Flags::TestWallSwitch));
+    harness.step(1);
+    assert_eq!(harness.get_positions(ResponseType::WALL), [Vector2D { x: 100, y: 50 }, Vector2D { x: 50, y: 100 }]);
+    harness.press(Button::RIGHT);
+    harness.press(Button::A);
+    harness.step(20);
+    assert!(harness.check_flag(Flags::TestWallSwitch));
+    assert_eq!(harness.get_positions(ResponseType::WALL), [Vector2D { x: 100, y: 50 }]);
+    harness.press(Button::A);
+    harness.step(20);
+    assert!(!harness.check_flag(Flags::TestWallSwitch));
+    assert_eq!(harness.get_positions(ResponseType::WALL).len(), 2);
+}
+
+#[test_case]
+fn meta_sprites_flip_as_one(gba: &mut Gba) {
+    // Only sets up the graphics, so the parts can be made into objects.
+    let _harness = TestHarness::new(gba, SCENES::Map001);
+    // A L shape of three parts, which only lines up again after a flip if every part is mirrored inside the bounds.
+    let mut sprite = MetaSprite::new();
+    sprite.add_part(sprites::WALL.sprite(0), Vector2D { x: 0, y: 0 });
+    sprite.add_part(sprites::WALL.sprite(0), Vector2D { x: 16, y: 0 });
+    sprite.add_part(sprites::WALL.sprite(0), Vector2D { x: 0, y: 16 });
+    sprite.set_pos(Vector2D { x: 100, y: 50 });
+    sprite.set_hflip(true);
+    assert_eq!(sprite.get_part_pos(0), Some(Vector2D { x: 116, y: 50 }));
+    assert_eq!(sprite.get_part_pos(1), Some(Vector2D { x: 100, y: 50 }));
+    assert_eq!(sprite.get_part_pos(2), Some(Vector2D { x: 116, y: 66 }));
+    sprite.set_vflip(true);
+    assert_eq!(sprite.get_part_pos(0), Some(Vector2D { x: 116, y: 66 }));
+    assert_eq!(sprite.get_part_pos(1), Some(Vector2D { x: 100, y: 66 }));
+    assert_eq!(sprite.get_part_pos(2), Some(Vector2D { x: 116, y: 50 }));
+    assert_eq!(sprite.get_collider(), Rect { position: Vector2D { x: 100, y: 50 }, size: Vector2D { x: 32, y: 32 } });
+}
+
+#[test_case]
+fn levers_flip_when_hit_by_swords(gba: &mut Gba) {
+    let mut harness = TestHarness::new(gba, SCENES::Map001);
+    harness.spawn(Actor::ATrigger(Vector2D { x: 66, y: 50 }, TriggerType::LEVER, Flags::TestWallSwitch));
+    harness.press(Button::RIGHT);
+    harness.press(Button::R);
+    harness.step(40);
+    assert!(harness.check_flag(Flags::TestWallSwitch));
+    harness.press(Button::R);
+    harness.step(40);
+    assert!(!harness.check_flag(Flags::TestWallSwitch));
+}
+
+#[test_case]
+fn bridges_cover_pits_once_the_flag_is_set(gba: &mut Gba) {
+    let mut harness = TestHarness::new(gba, SCENES::Map001);
+    harness.spawn(Actor::AGate(Vector2D { x: 66, y: 50 }, GateType::BRIDGE, Flags::TestWallSwitch));
+    harness.hold(Button::RIGHT, 10);
+    assert_eq!(harness.get_player_pos(), Vector2D { x: 50, y: 50 }, "Player walked into the pit");
+    harness.game_state.get_globals_mut().set_flag(Flags::TestWallSwitch, true);
+    harness.hold(Button::RIGHT, 20);
+    assert_eq!(harness.get_player_pos(), Vector2D { x: 70, y: 50 });
+}
+
+#[test_case]
+fn plates_set_flags_while_stood_on(gba: &mut Gba) {
+    let mut harness = TestHarness::new(gba, SCENES::Map001);
+    harness.spawn(Actor::ATrigger(Vector2D { x: 50, y: 50 }, TriggerType::PLATE, Flags::TestWallSwitch));
+    harness.step(3);
+    assert!(harness.check_flag(Flags::TestWallSwitch));
+    harness.hold(Button::DOWN, 30);
+    assert!(!harness.check_flag(Flags::TestWallSwitch));
+}
+
+#[test_case]
+fn plates_ignore_other_objects_passing(gba: &mut Gba) {
+    // Plates are sensors, so a shot crossing one neither stops on it nor lets it go while the player is still there.
+    let mut harness = TestHarness::new(gba, SCENES::Map001);
+    harness.spawn(Actor::ATrigger(Vector2D { x: 50, y: 50 }, TriggerType::PLATE, Flags::TestWallSwitch));
+    harness.press(Button::A);
+    harness.step(10);
+    assert!(harness.check_flag(Flags::TestWallSwitch));
+    assert_eq!(harness.get_positions(ResponseType::ATTACK).len(), 1, "Shot was stopped by the plate");
+}
+
+#[test_case]
+fn area_triggers_set_flags_once(gba: &mut Gba) {
+    let mut harness = TestHarness::new(gba, SCENES::Map001);
+    harness.spawn(Actor::ATrigger(Vector2D { x: 70, y: 40 }, TriggerType::AREA(Vector2D { x: 10, y: 40 }), Flags::UnimplementedFlag0));
+    harness.hold(Button::RIGHT, 10);
+    assert!(harness.check_flag(Flags::UnimplementedFlag0));
+    harness.hold(Button::LEFT, 20);
+    assert!(harness.check_flag(Flags::UnimplementedFlag0));
+}
+
+#[test_case]
+fn platforms_carry_riders(gba: &mut Gba) {
+    // The player starts standing on top of the platform.
+    let mut harness = TestHarness::new(gba, SCENES::Map001);
+    harness.spawn(Actor::APlatform(Vector2D { x: 40, y: 66 }, PlatformPath::LINEAR(Vector2D { x: 30, y: 0 }, 30), 2));
+    harness.step(40);
+    assert_eq!(harness.get_player_pos(), Vector2D { x: 80, y: 50 });
+}
+
+#[test_case]
+fn platforms_push_and_stop_at_walls(gba: &mut Gba) {
+    // The platform pushes the player left until they are pinned against the wall, then waits there.
+    let mut harness = TestHarness::new(gba, SCENES::Map001);
+    harness.spawn(Actor::AWall(Vector2D { x: 20, y: 50 }));
+    harness.spawn(Actor::APlatform(Vector2D { x: 80, y: 50 }, PlatformPath::LINEAR(Vector2D { x: -60, y: 0 }, 60), 1));
+    harness.step(70);
+    assert_eq!(harness.get_player_pos(), Vector2D { x: 36, y: 50 });
+    assert_eq!(harness.get_positions(ResponseType::WALL)[1], Vector2D { x: 52, y: 50 });
+}
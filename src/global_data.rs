@@ -7,9 +7,27 @@
 
 use agb::display::GraphicsFrame;
 use agb::{fixnum::Vector2D, input::ButtonController};
-use alloc::vec::Vec;
+use alloc::{boxed::Box, vec::Vec};
 use crate::actor;
-use crate::scene::{SCENES, BACKGROUNDS};
+use crate::game_obj::{GameObj, ResponseType};
+use crate::scene::{SCENES, BACKGROUNDS, TRACKS};
+use crate::sfx::{SFX, SfxRequest};
+use crate::music::{MusicCommand, MusicTransition};
+use agb::fixnum::Num;
+use agb::input::Button;
+use agb::save::SaveData;
+use crate::input_history::InputHistory;
+use crate::inventory::{Inventory, Items};
+use crate::input_map::{Actions, InputMap};
+use crate::input_replay::{InputLog, InputReplay, ReplayMode};
+use crate::input_state::{self, InputState};
+use crate::save_data::{SaveFile, SaveSection, SaveWriter};
+use crate::pathfinding::PathState;
+use crate::world_query::{RayHit, WorldQuery};
+
+// How many affine matrices the GBA can use in a single frame.
+const MAX_AFFINE_SLOTS: usize = 32;
+const MAX_PLAYER_HEALTH: u8 = 5;
 
 pub enum GAMEMODE {
     PLAY,
@@ -34,12 +52,29 @@ use agb::display::{
 pub(crate) struct GlobalData {
     flags: [bool; Flags::FlagMax as usize],
     next_scene: Option<SCENES>,
+    current_scene: SCENES,
     cam_offset: Vector2D<i32>,
+    scroll: Vector2D<i32>, // How far the camera has scrolled since the scene started.
+    player_pos: Option<Vector2D<i32>>,
+    player_health: u8,
+    checkpoint: Option<(SCENES, Vector2D<i32>)>,
+    respawn_pos: Option<Vector2D<i32>>,
+    inventory: Inventory,
+    world: WorldQuery,
     input_controller: ButtonController,
+    input_state: InputState,
+    input_replay: InputReplay,
+    input_map: InputMap,
+    replay_input_map: Option<InputMap>, // The recorded bindings, used in place of input_map while a replay plays.
+    input_history: InputHistory,
     current_mode: GAMEMODE,
     spawn_queue: Vec<actor::Actor>,
     bg: RegularBackground,
-    current_bg: Option<BACKGROUNDS>
+    current_bg: Option<BACKGROUNDS>,
+    affine_slots_used: usize,
+    sfx_queue: Vec<SfxRequest>,
+    music_queue: Vec<MusicCommand>,
+    save_file: SaveFile
 }
 
 impl GlobalData {
@@ -54,12 +89,29 @@ impl GlobalData {
         GlobalData {
             flags: [false; Flags::FlagMax as usize],
             next_scene: None,
+            current_scene: SCENES::TestScene,
             cam_offset: Vector2D { x: 0, y: 0 },
+            scroll: Vector2D { x: 0, y: 0 },
+            player_pos: None,
+            player_health: MAX_PLAYER_HEALTH,
+            checkpoint: None,
+            respawn_pos: None,
+            inventory: Inventory::new(),
+            world: WorldQuery::new(),
             input_controller: ButtonController::new(),
+            input_state: InputState::new(),
+            input_replay: InputReplay::new(),
+            input_map: InputMap::new(),
+            replay_input_map: None,
+            input_history: InputHistory::new(),
             current_mode: GAMEMODE::PLAY,
             spawn_queue: Vec::new(),
             bg: new_bg,
-            current_bg: None
+            current_bg: None,
+            affine_slots_used: 0,
+            sfx_queue: Vec::new(),
+            music_queue: Vec::new(),
+            save_file: SaveFile::new()
         }
     }
 
@@ -75,6 +127,57 @@ impl GlobalData {
         self.spawn_queue.clear();
     }
 
+    //Sound effect functions
+    pub fn play_sfx(&mut self, sound: SFX) {
+        self.sfx_queue.push(SfxRequest::from_sound(sound));
+    }
+
+    pub fn play_sfx_request(&mut self, request: SfxRequest) {
+        // For playing a sound effect with a non default priority, volume or panning.
+        self.sfx_queue.push(request);
+    }
+
+    pub fn take_sfx_queue(&mut self) -> Vec<SfxRequest> {
+        return core::mem::take(&mut self.sfx_queue);
+    }
+
+    //Music functions
+    pub fn play_music(&mut self, track: TRACKS, transition: MusicTransition) {
+        self.music_queue.push(MusicCommand::PLAY(track, transition));
+    }
+
+    pub fn stop_music(&mut self, transition: MusicTransition) {
+        self.music_queue.push(MusicCommand::STOP(transition));
+    }
+
+    pub fn pause_music(&mut self) {
+        self.music_queue.push(MusicCommand::PAUSE);
+    }
+
+    pub fn resume_music(&mut self) {
+        self.music_queue.push(MusicCommand::RESUME);
+    }
+
+    pub fn set_music_volume(&mut self, volume: Num<i16, 8>) {
+        self.music_queue.push(MusicCommand::VOLUME(volume));
+    }
+
+    pub fn set_music_tempo(&mut self, tempo: Num<u32, 8>) {
+        self.music_queue.push(MusicCommand::TEMPO(tempo));
+    }
+
+    pub fn jump_music_to_pattern(&mut self, pattern: usize) {
+        self.music_queue.push(MusicCommand::JUMP(pattern));
+    }
+
+    pub fn play_jingle(&mut self, track: TRACKS) {
+        self.music_queue.push(MusicCommand::JINGLE(track));
+    }
+
+    pub fn take_music_queue(&mut self) -> Vec<MusicCommand> {
+        return core::mem::take(&mut self.music_queue);
+    }
+
     pub fn get_mode(&self) -> &GAMEMODE {
         return &self.current_mode;
     }
@@ -115,16 +218,264 @@ impl GlobalData {
         return buff;
     }
 
-    pub fn get_input(&self) -> &ButtonController {
-        return &self.input_controller;
+    pub fn get_current_scene(&self) -> SCENES {
+        return self.current_scene;
+    }
+
+    pub fn set_current_scene(&mut self, scene: SCENES) {
+        // Called by the GameState when a scene is loaded.
+        self.current_scene = scene;
+    }
+
+    pub fn get_input(&self) -> &InputState {
+        return &self.input_state;
     }
 
     pub fn update_input(&mut self) {
+        // Reads the controller, then swaps in the recorded buttons if a replay is playing.
         self.input_controller.update();
+        let buttons = self.input_replay.next_frame(input_state::get_button_bits(&self.input_controller));
+        if self.input_replay.get_mode() != ReplayMode::PLAYING {
+            self.replay_input_map = None;
+        }
+        self.input_state.update(buttons);
+        self.input_history.push(buttons);
+    }
+
+    //Input replay functions
+    pub fn start_recording(&mut self, start_scene: SCENES) {
+        // Reloads the scene and records every frame of input from there.
+        self.reset_for_replay(start_scene);
+        self.input_replay.start_recording(start_scene, self.input_map);
+    }
+
+    pub fn start_replay(&mut self, log: InputLog) {
+        // Reloads the logs scene and plays the log back in place of the controller.
+        self.reset_for_replay(log.get_start_scene());
+        self.replay_input_map = Some(log.get_input_map());
+        self.input_replay.start_playing(log);
+    }
+
+    pub fn stop_replay(&mut self) -> InputLog {
+        // Stops recording or replaying, returns the log so a recording can be kept.
+        self.replay_input_map = None;
+        return self.input_replay.stop();
+    }
+
+    pub fn get_replay_mode(&self) -> ReplayMode {
+        return self.input_replay.get_mode();
+    }
+
+    #[cfg(test)]
+    pub fn inject_input(&mut self, log: InputLog) {
+        // Plays a log without reloading the scene, so tests can script input part way through a scene.
+        // The log is read with the current bindings.
+        self.replay_input_map = None;
+        self.input_replay.start_playing(log);
+    }
+
+    fn reset_for_replay(&mut self, start_scene: SCENES) {
+        // Anything kept between scenes that affects gameplay needs to be reset here, so replays start from the same state.
+        self.flags = [false; Flags::FlagMax as usize];
+        self.input_state = InputState::new();
+        self.input_history.clear();
+        self.spawn_queue.clear();
+        self.player_pos = None;
+        self.player_health = MAX_PLAYER_HEALTH;
+        self.checkpoint = None;
+        self.respawn_pos = None;
+        self.inventory.clear();
+        self.reset_offset();
+        self.reset_affine_slots();
+        self.replay_input_map = None;
+        self.music_queue.push(MusicCommand::RESET);
+        self.queue_scene_transition(start_scene);
+    }
+
+    //Input action functions
+    pub fn is_action_pressed(&self, action: Actions) -> bool {
+        return self.get_active_input_map().is_pressed(&self.input_state, action);
+    }
+
+    pub fn is_action_just_pressed(&self, action: Actions) -> bool {
+        return self.get_active_input_map().is_just_pressed(&self.input_state, action);
+    }
+
+    pub fn is_action_just_released(&self, action: Actions) -> bool {
+        return self.get_active_input_map().is_just_released(&self.input_state, action);
+    }
+
+    pub fn get_move_vector(&self) -> Vector2D<i32> {
+        return self.get_active_input_map().get_move_vector(&self.input_state);
+    }
+
+    pub fn get_input_history(&self) -> &InputHistory {
+        return &self.input_history;
+    }
+
+    pub fn consume_buffered_action(&mut self, action: Actions, window: usize) -> bool {
+        // True if the action was pressed in the last window frames, then consumes the press so it only triggers once.
+        return self.input_history.consume_buffered(self.get_active_input_map().get_binding(action), window);
+    }
+
+    pub fn is_action_double_tapped(&self, action: Actions, window: usize) -> bool {
+        return self.input_history.is_double_tapped(self.get_active_input_map().get_binding(action), window);
+    }
+
+    pub fn get_action_hold_frames(&self, action: Actions) -> u32 {
+        return self.input_history.get_hold_frames(self.get_active_input_map().get_binding(action));
+    }
+
+    pub fn get_action_charge_released(&self, action: Actions) -> Option<u32> {
+        // Returns how long the action was held for on the frame it is released.
+        return self.input_history.get_charge_released(self.get_active_input_map().get_binding(action));
+    }
+
+    pub fn check_action_combo(&self, sequence: &[Actions], window: usize) -> bool {
+        // (example: &[Actions::MoveDown, Actions::MoveRight, Actions::Attack] within 20 frames)
+        let buttons: Vec<Button> = sequence.iter().map(|action| self.get_active_input_map().get_binding(*action)).collect();
+        return self.input_history.check_combo(&buttons, window);
+    }
+
+    fn get_active_input_map(&self) -> &InputMap {
+        // Replays read their buttons with the bindings they were recorded with.
+        return self.replay_input_map.as_ref().unwrap_or(&self.input_map);
+    }
+
+    pub fn get_input_map(&self) -> &InputMap {
+        return &self.input_map;
+    }
+
+    pub fn rebind_action(&mut self, action: Actions, buttons: Button) {
+        // Call save_game() afterwards to keep the new binding.
+        self.input_map.set_binding(action, buttons);
+    }
+
+    pub fn reset_bindings(&mut self) {
+        self.input_map.reset_to_defaults();
+    }
+
+    //Save functions
+    pub fn set_save_media(&mut self, media: Option<SaveData>) {
+        self.save_file.set_media(media);
+    }
+
+    pub fn save_game(&mut self) -> Result<(), &'static str> {
+        let mut writer = SaveWriter::new();
+        writer.begin_section(SaveSection::InputBindings);
+        self.input_map.write_save(&mut writer);
+        writer.begin_section(SaveSection::Inventory);
+        self.inventory.write_save(&mut writer);
+        return self.save_file.write(writer);
+    }
+
+    pub fn load_game(&mut self) -> Result<(), &'static str> {
+        self.save_file.load()?;
+        if let Some(mut reader) = self.save_file.get_section(SaveSection::InputBindings) {
+            self.input_map.read_save(&mut reader);
+        }
+        if let Some(mut reader) = self.save_file.get_section(SaveSection::Inventory) {
+            self.inventory.read_save(&mut reader);
+        }
+        return Ok(());
+    }
+
+    //World query functions
+    pub fn update_world(&mut self, obj_box: &[Box<dyn GameObj>]) {
+        // Called by the GameState once all objects have moved, the casts below see the world as it was then.
+        self.world.rebuild(obj_box, self.get_world_offset());
+    }
+
+    pub fn reset_world(&mut self) {
+        // Called by the GameState when changing scenes, dropping any paths still being searched for.
+        self.world.clear();
+        self.scroll = Vector2D { x: 0, y: 0 };
+    }
+
+    pub fn cast_segment(&self, from: Vector2D<i32>, to: Vector2D<i32>, hits: &[ResponseType]) -> Option<RayHit> {
+        // Finds the first object using one of the hit ResponseTypes between the two points.
+        return self.world.cast_segment(from, to, hits, self.cam_offset);
+    }
+
+    pub fn cast_ray(&self, from: Vector2D<i32>, direction: Vector2D<i32>, distance: i32, hits: &[ResponseType]) -> Option<RayHit> {
+        // Like cast_segment(), but going the distance along a direction. (example: facing.get_vector())
+        // The length is found 256 times too big, so short diagonals like (1, 1) are not rounded down to 1.
+        let (x, y, distance) = (direction.x as i64, direction.y as i64, distance as i64);
+        let length = ((x * x + y * y) * 65536).isqrt().max(1);
+        let offset = Vector2D { x: (x * distance * 256 / length) as i32, y: (y * distance * 256 / length) as i32 };
+        return self.cast_segment(from, from + offset, hits);
+    }
+
+    pub fn has_line_of_sight(&self, from: Vector2D<i32>, to: Vector2D<i32>) -> bool {
+        // Only walls block sight.
+        return self.cast_segment(from, to, &[ResponseType::WALL]).is_none();
+    }
+
+    //Pathfinding functions, paths are in world space so they stay put while the camera scrolls.
+    pub fn find_path(&mut self, from: Vector2D<i32>, to: Vector2D<i32>) -> Option<u16> {
+        // Starts looking for a path between two screen positions, returns the id to check on it with or None if too many searches are running.
+        let world_offset = self.get_world_offset();
+        return self.world.find_path(from + world_offset, to + world_offset);
+    }
+
+    pub fn step_paths(&mut self) {
+        // Called by the GameState each frame to continue the searches a little further.
+        self.world.step_paths();
+    }
+
+    pub fn take_path(&mut self, id: u16) -> PathState {
+        // Gets the path once found, after which the id is forgotten.
+        return self.world.take_path(id);
+    }
+
+    pub fn get_world_offset(&self) -> Vector2D<i32> {
+        // Includes this frames scroll, which objects have already moved by during update().
+        return self.scroll + self.cam_offset;
+    }
+
+    pub fn to_world_pos(&self, screen_pos: Vector2D<i32>) -> Vector2D<i32> {
+        return screen_pos + self.get_world_offset();
+    }
+
+    pub fn to_screen_pos(&self, world_pos: Vector2D<i32>) -> Vector2D<i32> {
+        return world_pos - self.get_world_offset();
+    }
+
+    //Inventory functions
+    pub fn give_item(&mut self, item: Items, amount: u16) -> u16 {
+        // Returns how many were added, which can be less than the amount if the stack is full.
+        return self.inventory.add(item, amount);
+    }
+
+    pub fn take_item(&mut self, item: Items, amount: u16) -> bool {
+        // Returns false and takes nothing if there are not enough of the item. (example: spending coins in a shop)
+        return self.inventory.remove(item, amount);
+    }
+
+    pub fn get_item_count(&self, item: Items) -> u16 {
+        return self.inventory.get_count(item);
+    }
+
+    pub fn has_item(&self, item: Items) -> bool {
+        return self.inventory.has_item(item);
+    }
+
+    //Affine slot functions
+    pub fn request_affine_slot(&mut self) -> bool {
+        // Hands out one of the frames affine matrix slots, returns false if they are all used.
+        if self.affine_slots_used >= MAX_AFFINE_SLOTS {
+            return false;
+        }
+        self.affine_slots_used += 1;
+        return true;
+    }
+
+    pub fn reset_affine_slots(&mut self) {
+        self.affine_slots_used = 0;
     }
 
     //Flag functions
-    pub fn check_flag(self, entry: Flags) -> bool {
+    pub fn check_flag(&self, entry: Flags) -> bool {
         return self.flags[entry as usize]
     }
 
@@ -142,12 +493,72 @@ impl GlobalData {
     }
 
     pub fn reset_offset(&mut self) {
+        self.scroll += self.cam_offset;
         self.cam_offset = Vector2D::new(0, 0)
     }
+
+    //Player position functions
+    pub fn get_player_pos(&self) -> Option<Vector2D<i32>> {
+        // Where the player was last updated, None if there is no player in the scene. Used by enemies to find the player.
+        return self.player_pos;
+    }
+
+    pub fn set_player_pos(&mut self, pos: Option<Vector2D<i32>>) {
+        self.player_pos = pos;
+    }
+
+    //Player health functions
+    pub fn get_player_health(&self) -> u8 {
+        return self.player_health;
+    }
+
+    pub fn get_player_max_health(&self) -> u8 {
+        return MAX_PLAYER_HEALTH;
+    }
+
+    pub fn damage_player(&mut self, amount: u8) {
+        self.player_health = self.player_health.saturating_sub(amount);
+    }
+
+    pub fn heal_player(&mut self, amount: u8) {
+        self.player_health = self.player_health.saturating_add(amount).min(MAX_PLAYER_HEALTH);
+    }
+
+    pub fn set_checkpoint(&mut self, scene: SCENES, pos: Vector2D<i32>) {
+        // Where the player respawns after dying, the position is where they are placed when the scene is loaded.
+        self.checkpoint = Some((scene, pos));
+    }
+
+    pub fn get_checkpoint(&self) -> Option<(SCENES, Vector2D<i32>)> {
+        return self.checkpoint;
+    }
+
+    pub fn respawn_player(&mut self) {
+        // Restores the players health then reloads the checkpoints scene, or the current scene from its entry if there is no checkpoint.
+        self.player_health = MAX_PLAYER_HEALTH;
+        match self.checkpoint {
+            Some((scene, pos)) => {
+                self.respawn_pos = Some(pos);
+                self.queue_scene_transition(scene);
+            },
+            None => {
+                self.respawn_pos = None;
+                self.queue_scene_transition(self.current_scene);
+            },
+        }
+    }
+
+    pub fn take_respawn_pos(&mut self) -> Option<Vector2D<i32>> {
+        // Taken by the player on its first update after respawning.
+        return self.respawn_pos.take();
+    }
 }
 
+#[derive(Clone, Copy, PartialEq)]
 pub enum Flags {
     TestWallSwitch,
+    TestBridgeSwitch,
+    TestTrapArea,
     UnimplementedFlag0,
     UnimplementedFlag1,
     FlagMax //This should always be last.
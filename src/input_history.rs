@@ -0,0 +1,147 @@
+#![allow(dead_code, unused_variables)]
+
+/*
+    A ring buffer of the last HISTORY_LENGTH frames of button state, updated with the controller.
+    Used for input buffering (accepting a jump pressed a few frames early), double taps,
+    charge/hold durations and combo sequences.
+    Frame 0 is the current frame, frame 1 is the one before it and so on.
+*/
+
+use agb::input::Button;
+
+pub const HISTORY_LENGTH: usize = 32;
+const BUTTON_COUNT: usize = 10;
+
+pub(crate) struct InputHistory {
+    frames: [u16; HISTORY_LENGTH],
+    consumed: [u16; HISTORY_LENGTH],
+    head: usize,
+    hold_frames: [u32; BUTTON_COUNT],
+    released_hold_frames: [u32; BUTTON_COUNT]
+}
+
+impl InputHistory {
+    pub fn new() -> InputHistory {
+        InputHistory {
+            frames: [0; HISTORY_LENGTH],
+            consumed: [0; HISTORY_LENGTH],
+            head: 0,
+            hold_frames: [0; BUTTON_COUNT],
+            released_hold_frames: [0; BUTTON_COUNT]
+        }
+    }
+
+    pub fn push(&mut self, buttons: u16) {
+        // Should be called once per frame after the controller updates.
+        self.head = (self.head + 1) % HISTORY_LENGTH;
+        self.frames[self.head] = buttons;
+        self.consumed[self.head] = 0;
+        for index in 0..BUTTON_COUNT {
+            if buttons & (1 << index) != 0 {
+                self.hold_frames[index] += 1;
+            } else {
+                self.released_hold_frames[index] = self.hold_frames[index];
+                self.hold_frames[index] = 0;
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = InputHistory::new();
+    }
+
+    pub fn get_frame(&self, frames_ago: usize) -> u16 {
+        if frames_ago >= HISTORY_LENGTH {
+            return 0;
+        }
+        return self.frames[(self.head + HISTORY_LENGTH - frames_ago) % HISTORY_LENGTH];
+    }
+
+    pub fn was_just_pressed(&self, buttons: Button, frames_ago: usize) -> bool {
+        // If any of the buttons went from released to pressed on that frame, and it has not been consumed.
+        if frames_ago + 1 >= HISTORY_LENGTH {
+            return false;
+        }
+        let mask = buttons.bits() as u16;
+        let index = (self.head + HISTORY_LENGTH - frames_ago) % HISTORY_LENGTH;
+        let pressed = self.frames[index] & mask & !self.consumed[index];
+        let previous = self.get_frame(frames_ago + 1) & mask;
+        return pressed != 0 && previous == 0;
+    }
+
+    pub fn consume_buffered(&mut self, buttons: Button, window: usize) -> bool {
+        // Checks for a press in the last window frames, and consumes it so the same press is not used twice.
+        // (example: a jump pressed 5 frames before landing is still accepted with a window of 5)
+        for frames_ago in 0..=window.min(HISTORY_LENGTH - 2) {
+            if self.was_just_pressed(buttons, frames_ago) {
+                let index = (self.head + HISTORY_LENGTH - frames_ago) % HISTORY_LENGTH;
+                self.consumed[index] |= buttons.bits() as u16;
+                return true;
+            }
+        }
+        return false;
+    }
+
+    pub fn is_double_tapped(&self, buttons: Button, window: usize) -> bool {
+        // True on the frame of the second press, if the first press was within window frames of it.
+        if !self.was_just_pressed(buttons, 0) {
+            return false;
+        }
+        for frames_ago in 1..=window.min(HISTORY_LENGTH - 2) {
+            if self.was_just_pressed(buttons, frames_ago) {
+                return true;
+            }
+        }
+        return false;
+    }
+
+    pub fn get_hold_frames(&self, buttons: Button) -> u32 {
+        // How many frames the buttons have been held for, the longest held one if there are several.
+        return self.get_longest(&self.hold_frames, buttons);
+    }
+
+    pub fn get_charge_released(&self, buttons: Button) -> Option<u32> {
+        // On the frame the buttons are released, returns how long they were held for. Used for charge attacks.
+        let mask = buttons.bits() as u16;
+        let released = self.get_frame(1) & mask != 0 && self.get_frame(0) & mask == 0;
+        if !released {
+            return None;
+        }
+        return Some(self.get_longest(&self.released_hold_frames, buttons));
+    }
+
+    pub fn check_combo(&self, sequence: &[Button], window: usize) -> bool {
+        // True on the frame the last button in the sequence is pressed, if each earlier button
+        // was pressed in order and the whole sequence fit inside window frames.
+        let Some((last, earlier)) = sequence.split_last() else {
+            return false;
+        };
+        if !self.was_just_pressed(*last, 0) {
+            return false;
+        }
+        let mut frames_ago = 1;
+        for buttons in earlier.iter().rev() {
+            loop {
+                if frames_ago > window || frames_ago + 1 >= HISTORY_LENGTH {
+                    return false;
+                }
+                if self.was_just_pressed(*buttons, frames_ago) {
+                    break;
+                }
+                frames_ago += 1;
+            }
+            frames_ago += 1;
+        }
+        return true;
+    }
+
+    fn get_longest(&self, counters: &[u32; BUTTON_COUNT], buttons: Button) -> u32 {
+        let mut longest = 0;
+        for (index, frames) in counters.iter().enumerate() {
+            if buttons.bits() & (1 << index) != 0 {
+                longest = longest.max(*frames);
+            }
+        }
+        return longest;
+    }
+}
@@ -0,0 +1,151 @@
+#![allow(dead_code, unused_variables)]
+
+/*
+    Maps game actions to buttons, so objects check what the player wants to do
+    instead of which button was pressed. Bindings can be changed at runtime
+    and are stored in the save file.
+    Each action can be bound to more than one button. (example: Button::A | Button::START)
+*/
+
+use agb::input::Button;
+use agb::fixnum::Vector2D;
+use alloc::vec::Vec;
+use crate::input_state::InputState;
+use crate::save_data::{SaveReader, SaveWriter};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Actions {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Jump,
+    Attack,
+    Confirm,
+    Cancel,
+    Swing,
+    ActionMax //This should always be last.
+}
+
+fn get_default_binding(action: Actions) -> Button {
+    match action {
+        Actions::MoveUp => return Button::UP,
+        Actions::MoveDown => return Button::DOWN,
+        Actions::MoveLeft => return Button::LEFT,
+        Actions::MoveRight => return Button::RIGHT,
+        Actions::Jump => return Button::B,
+        Actions::Attack => return Button::A,
+        Actions::Confirm => return Button::A | Button::START,
+        Actions::Cancel => return Button::SELECT, // Kept off B, so it never fires along with Jump.
+        Actions::Swing => return Button::R,
+        Actions::ActionMax => return Button::empty(),
+    }
+}
+
+const ALL_ACTIONS: [Actions; Actions::ActionMax as usize] = [
+    Actions::MoveUp,
+    Actions::MoveDown,
+    Actions::MoveLeft,
+    Actions::MoveRight,
+    Actions::Jump,
+    Actions::Attack,
+    Actions::Confirm,
+    Actions::Cancel,
+    Actions::Swing
+];
+
+// How many bytes write_bytes() uses, two for each action.
+pub const INPUT_MAP_BYTES: usize = Actions::ActionMax as usize * 2;
+
+#[derive(Clone, Copy)]
+pub(crate) struct InputMap {
+    bindings: [Button; Actions::ActionMax as usize]
+}
+
+impl InputMap {
+    pub fn new() -> InputMap {
+        let mut input_map = InputMap {
+            bindings: [Button::empty(); Actions::ActionMax as usize]
+        };
+        input_map.reset_to_defaults();
+        return input_map;
+    }
+
+    pub fn reset_to_defaults(&mut self) {
+        for action in ALL_ACTIONS {
+            self.bindings[action as usize] = get_default_binding(action);
+        }
+    }
+
+    pub fn get_binding(&self, action: Actions) -> Button {
+        return self.bindings[action as usize];
+    }
+
+    pub fn set_binding(&mut self, action: Actions, buttons: Button) {
+        self.bindings[action as usize] = buttons;
+    }
+
+    pub fn is_pressed(&self, input: &InputState, action: Actions) -> bool {
+        return input.is_pressed(self.get_binding(action));
+    }
+
+    pub fn is_just_pressed(&self, input: &InputState, action: Actions) -> bool {
+        return input.is_just_pressed(self.get_binding(action));
+    }
+
+    pub fn is_just_released(&self, input: &InputState, action: Actions) -> bool {
+        return input.is_just_released(self.get_binding(action));
+    }
+
+    pub fn get_move_vector(&self, input: &InputState) -> Vector2D<i32> {
+        // Gets the direction from the Move actions, up and left win if both directions on a axis are held.
+        let mut move_dir: Vector2D<i32> = Vector2D { x: 0, y: 0 };
+        if self.is_pressed(input, Actions::MoveUp) {
+            move_dir.y = -1;
+        } else if self.is_pressed(input, Actions::MoveDown) {
+            move_dir.y = 1;
+        }
+
+        if self.is_pressed(input, Actions::MoveLeft) {
+            move_dir.x = -1;
+        } else if self.is_pressed(input, Actions::MoveRight) {
+            move_dir.x = 1;
+        }
+        return move_dir;
+    }
+
+    pub fn write_save(&self, writer: &mut SaveWriter) {
+        for binding in self.bindings {
+            writer.write_u16(binding.bits() as u16);
+        }
+    }
+
+    pub fn read_save(&mut self, reader: &mut SaveReader) {
+        // Keeps the default binding for any action missing from the save, or saved without any buttons.
+        for action in ALL_ACTIONS {
+            if let Some(bits) = reader.read_u16() {
+                let buttons = Button::from_bits_truncate(bits as u32);
+                if !buttons.is_empty() {
+                    self.bindings[action as usize] = buttons;
+                }
+            }
+        }
+    }
+
+    pub fn write_bytes(&self, bytes: &mut Vec<u8>) {
+        // Little endian buttons for each action, used to keep the bindings with a input recording.
+        for binding in self.bindings {
+            bytes.extend_from_slice(&(binding.bits() as u16).to_le_bytes());
+        }
+    }
+
+    pub fn read_bytes(&mut self, bytes: &[u8]) {
+        // Follows the same rules as read_save().
+        for (action, chunk) in ALL_ACTIONS.iter().zip(bytes.chunks_exact(2)) {
+            let buttons = Button::from_bits_truncate(u16::from_le_bytes([chunk[0], chunk[1]]) as u32);
+            if !buttons.is_empty() {
+                self.bindings[*action as usize] = buttons;
+            }
+        }
+    }
+}
@@ -0,0 +1,157 @@
+#![allow(dead_code, unused_variables)]
+
+/*
+    Records the button state of every frame into a InputLog, and plays a log back in place of the controller.
+    Logs store runs of identical frames, so holding a button for a long time only takes one entry.
+    Recording and replaying both start from a fresh load of the logs scene, so a replay reproduces
+    the recorded session exactly as long as the game loop only depends on the input it is given.
+    Logs also keep the bindings used while recording, which are swapped in during playback so rebinding
+    a action afterwards does not change what the replay does.
+    Logs can be turned into bytes to be kept in a bug report or included with include_bytes! for attract mode demos.
+*/
+
+use alloc::vec::Vec;
+use crate::input_map::{INPUT_MAP_BYTES, InputMap};
+use crate::scene::SCENES;
+
+#[derive(Clone, Copy)]
+struct InputRun {
+    buttons: u16,
+    frames: u16
+}
+
+#[derive(Clone)]
+pub(crate) struct InputLog {
+    start_scene: SCENES,
+    input_map: InputMap,
+    runs: Vec<InputRun>
+}
+
+impl InputLog {
+    pub fn new(start_scene: SCENES, input_map: InputMap) -> InputLog {
+        InputLog {
+            start_scene,
+            input_map,
+            runs: Vec::new()
+        }
+    }
+
+    pub fn get_start_scene(&self) -> SCENES {
+        return self.start_scene;
+    }
+
+    pub fn get_input_map(&self) -> InputMap {
+        return self.input_map;
+    }
+
+    pub fn push(&mut self, buttons: u16) {
+        if let Some(last) = self.runs.last_mut() && last.buttons == buttons && last.frames < u16::MAX {
+            last.frames += 1;
+            return;
+        }
+        self.runs.push(InputRun { buttons, frames: 1 });
+    }
+
+    pub fn get_frame_count(&self) -> usize {
+        return self.runs.iter().map(|run| run.frames as usize).sum();
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        // The scene index, the bindings, then each run as little endian buttons and frame count.
+        let mut bytes = Vec::with_capacity(1 + INPUT_MAP_BYTES + self.runs.len() * 4);
+        bytes.push(self.start_scene as u8);
+        self.input_map.write_bytes(&mut bytes);
+        for run in &self.runs {
+            bytes.extend_from_slice(&run.buttons.to_le_bytes());
+            bytes.extend_from_slice(&run.frames.to_le_bytes());
+        }
+        return bytes;
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<InputLog> {
+        let (scene_index, bytes) = bytes.split_first()?;
+        let (binding_bytes, run_bytes) = bytes.split_at_checked(INPUT_MAP_BYTES)?;
+        let mut input_map = InputMap::new();
+        input_map.read_bytes(binding_bytes);
+        let mut log = InputLog::new(SCENES::from_index(*scene_index as usize)?, input_map);
+        for chunk in run_bytes.chunks_exact(4) {
+            log.runs.push(InputRun {
+                buttons: u16::from_le_bytes([chunk[0], chunk[1]]),
+                frames: u16::from_le_bytes([chunk[2], chunk[3]])
+            });
+        }
+        return Some(log);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ReplayMode {
+    OFF,
+    RECORDING,
+    PLAYING
+}
+
+pub(crate) struct InputReplay {
+    mode: ReplayMode,
+    log: InputLog,
+    run_index: usize,
+    run_frame: u16
+}
+
+impl InputReplay {
+    pub fn new() -> InputReplay {
+        InputReplay {
+            mode: ReplayMode::OFF,
+            log: InputLog::new(SCENES::TestScene, InputMap::new()),
+            run_index: 0,
+            run_frame: 0
+        }
+    }
+
+    pub fn get_mode(&self) -> ReplayMode {
+        return self.mode;
+    }
+
+    pub fn start_recording(&mut self, start_scene: SCENES, input_map: InputMap) {
+        self.mode = ReplayMode::RECORDING;
+        self.log = InputLog::new(start_scene, input_map);
+    }
+
+    pub fn start_playing(&mut self, log: InputLog) {
+        self.mode = ReplayMode::PLAYING;
+        self.log = log;
+        self.run_index = 0;
+        self.run_frame = 0;
+    }
+
+    pub fn stop(&mut self) -> InputLog {
+        // Stops recording or playing, and returns the log that was used.
+        self.mode = ReplayMode::OFF;
+        return core::mem::replace(&mut self.log, InputLog::new(SCENES::TestScene, InputMap::new()));
+    }
+
+    pub fn next_frame(&mut self, hardware_buttons: u16) -> u16 {
+        // Takes the real buttons for this frame, and returns the buttons the game should use.
+        match self.mode {
+            ReplayMode::OFF => return hardware_buttons,
+            ReplayMode::RECORDING => {
+                self.log.push(hardware_buttons);
+                return hardware_buttons;
+            },
+            ReplayMode::PLAYING => {
+                let Some(run) = self.log.runs.get(self.run_index) else {
+                    // The log has finished, so control goes back to the player.
+                    self.mode = ReplayMode::OFF;
+                    return hardware_buttons;
+                };
+                let buttons = run.buttons;
+                self.run_frame += 1;
+                if self.run_frame >= run.frames {
+                    self.run_index += 1;
+                    self.run_frame = 0;
+                }
+                return buttons;
+            },
+        }
+    }
+}
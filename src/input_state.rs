@@ -0,0 +1,61 @@
+#![allow(dead_code, unused_variables)]
+
+/*
+    The button state objects read from each frame.
+    It is filled from the hardware controller, or from a recording when replaying,
+    so nothing in the game reads the buttons directly.
+*/
+
+use agb::input::{Button, ButtonController};
+
+const BUTTON_COUNT: usize = 10;
+
+pub fn get_button_bits(controller: &ButtonController) -> u16 {
+    // Gets the pressed buttons as a bitmask matching agb's Button values.
+    let mut bits = 0;
+    for index in 0..BUTTON_COUNT {
+        if controller.is_pressed(Button::from_bits_truncate(1 << index)) {
+            bits |= 1 << index;
+        }
+    }
+    return bits;
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct InputState {
+    current: u16,
+    previous: u16
+}
+
+impl InputState {
+    pub fn new() -> InputState {
+        InputState {
+            current: 0,
+            previous: 0
+        }
+    }
+
+    pub fn update(&mut self, buttons: u16) {
+        self.previous = self.current;
+        self.current = buttons;
+    }
+
+    pub fn get_bits(&self) -> u16 {
+        return self.current;
+    }
+
+    pub fn is_pressed(&self, buttons: Button) -> bool {
+        // True if any of the buttons are pressed.
+        return self.current & buttons.bits() as u16 != 0;
+    }
+
+    pub fn is_just_pressed(&self, buttons: Button) -> bool {
+        let keys = buttons.bits() as u16;
+        return (self.current & keys != 0) && (self.previous & keys == 0);
+    }
+
+    pub fn is_just_released(&self, buttons: Button) -> bool {
+        let keys = buttons.bits() as u16;
+        return (self.current & keys == 0) && (self.previous & keys != 0);
+    }
+}
@@ -0,0 +1,93 @@
+#![allow(dead_code, unused_variables)]
+
+/*
+    The players inventory, a count of each item they are holding.
+    Each item has a definition with its name and how many can be held at once,
+    and the counts are kept in the save file.
+*/
+
+use crate::save_data::{SaveReader, SaveWriter};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Items {
+    Coin,
+    Key,
+    Potion,
+    ItemMax //This should always be last.
+}
+
+pub struct ItemData {
+    pub name: &'static str,
+    pub max_stack: u16
+}
+
+pub fn get_item_data(item: Items) -> ItemData {
+    match item {
+        Items::Coin => return ItemData { name: "Coin", max_stack: 999 },
+        Items::Key => return ItemData { name: "Key", max_stack: 9 },
+        Items::Potion => return ItemData { name: "Potion", max_stack: 5 },
+        Items::ItemMax => return ItemData { name: "", max_stack: 0 },
+    }
+}
+
+const ALL_ITEMS: [Items; Items::ItemMax as usize] = [
+    Items::Coin,
+    Items::Key,
+    Items::Potion
+];
+
+pub(crate) struct Inventory {
+    counts: [u16; Items::ItemMax as usize]
+}
+
+impl Inventory {
+    pub fn new() -> Inventory {
+        Inventory {
+            counts: [0; Items::ItemMax as usize]
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.counts = [0; Items::ItemMax as usize];
+    }
+
+    pub fn add(&mut self, item: Items, amount: u16) -> u16 {
+        // Adds up to the items max stack, returns how many were actually added.
+        let count = &mut self.counts[item as usize];
+        let added = amount.min(get_item_data(item).max_stack.saturating_sub(*count));
+        *count += added;
+        return added;
+    }
+
+    pub fn remove(&mut self, item: Items, amount: u16) -> bool {
+        // Only removes the items if there are enough of them.
+        let count = &mut self.counts[item as usize];
+        if *count < amount {
+            return false;
+        }
+        *count -= amount;
+        return true;
+    }
+
+    pub fn get_count(&self, item: Items) -> u16 {
+        return self.counts[item as usize];
+    }
+
+    pub fn has_item(&self, item: Items) -> bool {
+        return self.get_count(item) > 0;
+    }
+
+    pub fn write_save(&self, writer: &mut SaveWriter) {
+        for count in self.counts {
+            writer.write_u16(count);
+        }
+    }
+
+    pub fn read_save(&mut self, reader: &mut SaveReader) {
+        // Items missing from the save are left empty.
+        for item in ALL_ITEMS {
+            let count = reader.read_u16().unwrap_or(0);
+            self.counts[item as usize] = count.min(get_item_data(item).max_stack);
+        }
+    }
+}
@@ -5,29 +5,44 @@
 #![cfg_attr(test, test_runner(agb::test_runner::test_runner))]
 
 use agb::sound::mixer::Frequency;
-use agb_tracker::{Track, include_xm, Tracker};
 extern crate alloc;
 
-static BGM: Track = include_xm!("bgm/bgm.xm");
-
 mod game_state;
 mod scene;
 mod actor;
 mod game_obj;
 mod global_data;
+mod animation;
+mod game_sprite;
+mod meta_sprite;
+mod sfx;
+mod music;
+mod input_map;
+mod input_history;
+mod input_state;
+mod input_replay;
+mod save_data;
+mod inventory;
+mod collision;
+mod world_query;
+mod pathfinding;
+#[cfg(test)]
+mod game_tests;
 
 #[agb::entry]
 fn main(mut gba: agb::Gba) -> ! {
     let mut gfx = gba.graphics.get();  
     let mut mixer = gba.mixer.mixer(Frequency::Hz32768);
+    gba.save.init_sram();
     let mut game_state = game_state::GameState::new();
+    game_state.load_save(gba.save.access().ok());
     game_state.change_scene(scene::SCENES::TestScene);
 
-    let mut tracker = Tracker::new(&BGM);
     loop {
         let mut frame = gfx.frame();
         game_state.cycle_update(&mut frame);
-        tracker.step(&mut mixer);
+        game_state.process_sfx(&mut mixer);
+        game_state.process_music(&mut mixer);
         mixer.frame();
         frame.commit();
     }
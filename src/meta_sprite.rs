@@ -0,0 +1,140 @@
+#![allow(dead_code, unused_variables)]
+
+/*
+    A logical sprite made out of several hardware objects, for actors larger than the 64x64 hardware limit.
+    Each part has a offset from the metasprites position, flipping mirrors the parts
+    inside the combined bounds so the whole sprite flips as one.
+    Actors using one should override on_screen() with get_collider(), as the default only allows 16 pixels off screen.
+*/
+
+use agb::display::object::{Object, Sprite, Tag};
+use agb::display::{GraphicsFrame, Priority};
+use agb::fixnum::{Rect, Vector2D};
+use alloc::vec::Vec;
+
+struct MetaSpritePart {
+    object: Object,
+    offset: Vector2D<i32>,
+    size: Vector2D<i32>
+}
+
+pub(crate) struct MetaSprite {
+    parts: Vec<MetaSpritePart>,
+    pos: Vector2D<i32>,
+    bounds: Rect<i32>,
+    hflip: bool,
+    vflip: bool
+}
+
+impl MetaSprite {
+    pub fn new() -> MetaSprite {
+        MetaSprite {
+            parts: Vec::new(),
+            pos: Vector2D { x: 0, y: 0 },
+            bounds: Rect { position: Vector2D { x: 0, y: 0 }, size: Vector2D { x: 0, y: 0 } },
+            hflip: false,
+            vflip: false
+        }
+    }
+
+    pub fn from_grid(tag: &'static Tag, columns: usize) -> MetaSprite {
+        // Builds a metasprite from a tag where each frame is one part, laid out left to right then top to bottom.
+        // Every frame in the tag should be the same size. (example: a 128x128 boss split into four 64x64 frames with 2 columns)
+        let mut meta_sprite = MetaSprite::new();
+        for (index, sprite) in tag.sprites().iter().enumerate() {
+            let (width, height) = sprite.size().to_width_height();
+            let offset = Vector2D {
+                x: (index % columns) as i32 * width as i32,
+                y: (index / columns) as i32 * height as i32,
+            };
+            meta_sprite.add_part(sprite, offset);
+        }
+        return meta_sprite;
+    }
+
+    pub fn add_part(&mut self, sprite: &'static Sprite, offset: Vector2D<i32>) {
+        let (width, height) = sprite.size().to_width_height();
+        let part = MetaSpritePart {
+            object: Object::new(sprite),
+            offset,
+            size: Vector2D { x: width as i32, y: height as i32 }
+        };
+        self.parts.push(part);
+        self.update_bounds();
+        self.update_parts();
+    }
+
+    pub fn set_part_sprite(&mut self, index: usize, sprite: &'static Sprite) {
+        // The new sprite should be the same size as the old one, otherwise the bounds will be wrong.
+        if let Some(part) = self.parts.get_mut(index) {
+            part.object.set_sprite(sprite);
+        }
+    }
+
+    pub fn set_pos(&mut self, pos: Vector2D<i32>) {
+        self.pos = pos;
+        self.update_parts();
+    }
+
+    pub fn set_hflip(&mut self, flip: bool) {
+        self.hflip = flip;
+        self.update_parts();
+    }
+
+    pub fn set_vflip(&mut self, flip: bool) {
+        self.vflip = flip;
+        self.update_parts();
+    }
+
+    pub fn set_priority(&mut self, priority: Priority) {
+        for part in &mut self.parts {
+            part.object.set_priority(priority);
+        }
+    }
+
+    #[cfg(test)]
+    pub fn get_part_pos(&self, index: usize) -> Option<Vector2D<i32>> {
+        return self.parts.get(index).map(|part| part.object.pos());
+    }
+
+    pub fn get_collider(&self) -> Rect<i32> {
+        // The combined area covered by every part.
+        return Rect { position: self.pos + self.bounds.position, size: self.bounds.size };
+    }
+
+    pub fn show(&self, frame: &mut GraphicsFrame) {
+        for part in &self.parts {
+            part.object.show(frame);
+        }
+    }
+
+    fn update_bounds(&mut self) {
+        let mut top_left = self.parts[0].offset;
+        let mut bottom_right = self.parts[0].offset + self.parts[0].size;
+        for part in &self.parts {
+            top_left.x = top_left.x.min(part.offset.x);
+            top_left.y = top_left.y.min(part.offset.y);
+            bottom_right.x = bottom_right.x.max(part.offset.x + part.size.x);
+            bottom_right.y = bottom_right.y.max(part.offset.y + part.size.y);
+        }
+        self.bounds = Rect { position: top_left, size: bottom_right - top_left };
+    }
+
+    fn update_parts(&mut self) {
+        // Mirrors each part inside the bounds when flipped.
+        let bounds_start = self.bounds.position;
+        let bounds_end = self.bounds.position + self.bounds.size;
+        for part in &mut self.parts {
+            let mut offset = part.offset;
+            if self.hflip {
+                offset.x = bounds_start.x + bounds_end.x - (part.offset.x + part.size.x);
+            }
+            if self.vflip {
+                offset.y = bounds_start.y + bounds_end.y - (part.offset.y + part.size.y);
+            }
+            part.object.set_pos(self.pos + offset);
+            part.object.set_hflip(self.hflip);
+            part.object.set_vflip(self.vflip);
+        }
+    }
+}
@@ -0,0 +1,419 @@
+#![allow(dead_code, unused_variables)]
+
+/*
+    The music manager, contains the BGM tracks and plays the one the current scene asks for.
+    When the scene changes the track can cut, crossfade, or fade out then fade in.
+    If the next scene uses the same track it keeps playing without restarting.
+    Objects control the music by queueing MusicCommands through GlobalData.
+*/
+
+use agb::fixnum::{Num, num};
+use agb::sound::mixer::{ChannelId, Mixer, SoundChannel};
+use agb_tracker::{Track, Tracker, include_xm};
+use alloc::borrow::Cow;
+use crate::scene::TRACKS;
+
+static BGM: Track = include_xm!("bgm/bgm.xm");
+
+const SEEK_STEPS_PER_FRAME: u32 = 64; // How far a jump fast forwards each frame, so a long seek is spread out instead of stalling one frame.
+
+fn get_track_data(track: TRACKS) -> &'static Track {
+    match track {
+        TRACKS::Bgm1 => return &BGM,
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum MusicTransition {
+    CUT, // Stops the old track and starts the new one straight away.
+    CROSSFADE(u16), // Fades the old track out while the new one fades in, over this many frames.
+    FADE(u16) // Fades the old track out over this many frames, then fades the new one in over the same amount.
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum MusicCommand {
+    PLAY(TRACKS, MusicTransition),
+    STOP(MusicTransition),
+    PAUSE,
+    RESUME,
+    VOLUME(Num<i16, 8>), // 0 to 1, scales every track.
+    TEMPO(Num<u32, 8>), // 1 is normal speed, 2 is double speed.
+    JUMP(usize), // Restarts the current track from this entry in its pattern order.
+    JINGLE(TRACKS), // Plays the track through once, then resumes the previous track where it left off.
+    RESET // Stops everything and puts the volume, tempo and pause back to normal, any queued track still plays.
+}
+
+struct PlayingTrack {
+    track: TRACKS,
+    tracker: Tracker,
+    volume: Num<i32, 16>,
+    fade_speed: Num<i32, 16>,
+    seek_steps_left: u32 // Steps still to fast forward through after a jump, the track is silent until they are done.
+}
+
+impl PlayingTrack {
+    fn new(track: TRACKS, fade_in_frames: u16) -> PlayingTrack {
+        let (volume, fade_speed) = match fade_in_frames {
+            0 => (num!(1), num!(0)),
+            frames => (num!(0), get_fade_speed(frames)),
+        };
+        PlayingTrack {
+            track,
+            tracker: Tracker::new(get_track_data(track)),
+            volume,
+            fade_speed,
+            seek_steps_left: 0
+        }
+    }
+
+    fn fade_out(&mut self, frames: u16) {
+        self.fade_speed = -get_fade_speed(frames);
+    }
+
+    fn step(&mut self, mixer: &mut Mixer<'_>, master_volume: Num<i16, 8>, steps: u32) {
+        // Fades once per frame, but steps the tracker as many times as the tempo asks for.
+        self.volume = (self.volume + self.fade_speed).clamp(num!(0), num!(1));
+        if self.seek_steps_left > 0 {
+            let seek_steps = self.seek_steps_left.min(SEEK_STEPS_PER_FRAME);
+            let mut null_mixer = NullMixer {};
+            for _ in 0..seek_steps {
+                self.tracker.step(&mut null_mixer);
+            }
+            self.seek_steps_left -= seek_steps;
+            return;
+        }
+        let mut scaled_mixer = ScaledMixer::new(mixer, self.get_mixer_volume() * master_volume);
+        for _ in 0..steps {
+            self.tracker.step(&mut scaled_mixer);
+        }
+    }
+
+    fn jump_to_pattern(&mut self, mixer: &mut Mixer<'_>, pattern: usize) {
+        // The tracker can not seek, so this restarts it and silently steps it forward to the start of the pattern over the next few frames.
+        self.tracker.stop(mixer);
+        self.tracker = Tracker::new(get_track_data(self.track));
+        self.seek_steps_left = get_steps_until_pattern(self.track, pattern);
+    }
+
+    fn is_faded_out(&self) -> bool {
+        return self.fade_speed < num!(0) && self.volume == num!(0);
+    }
+
+    fn get_mixer_volume(&self) -> Num<i16, 8> {
+        return Num::from_raw((self.volume.to_raw() >> 8) as i16);
+    }
+}
+
+fn get_fade_speed(frames: u16) -> Num<i32, 16> {
+    return num!(1) / Num::new(frames.max(1) as i32);
+}
+
+fn get_steps_until_pattern(track: TRACKS, pattern: usize) -> u32 {
+    // How many tracker steps it takes to reach the pattern, ignores any speed changes made by effects inside the track.
+    let track_data = get_track_data(track);
+    let steps_per_row = track_data.frames_per_tick * track_data.ticks_per_step;
+    let mut steps: Num<u32, 8> = num!(0);
+    for pattern_index in track_data.patterns_to_play.iter().take(pattern) {
+        steps += steps_per_row * track_data.patterns[*pattern_index].length as u32;
+    }
+    return steps.floor();
+}
+
+pub(crate) struct MusicPlayer {
+    current: Option<PlayingTrack>,
+    fading_out: Option<PlayingTrack>,
+    waiting: Option<(TRACKS, u16)>,
+    queued: Option<(Option<TRACKS>, MusicTransition)>,
+    after_jingle: Option<PlayingTrack>,
+    jingle_steps_left: u32,
+    volume: Num<i16, 8>,
+    tempo: Num<u32, 8>,
+    tempo_counter: Num<u32, 8>,
+    paused: bool
+}
+
+impl MusicPlayer {
+    pub fn new() -> MusicPlayer {
+        MusicPlayer {
+            current: None,
+            fading_out: None,
+            waiting: None,
+            queued: None,
+            after_jingle: None,
+            jingle_steps_left: 0,
+            volume: num!(1),
+            tempo: num!(1),
+            tempo_counter: num!(0),
+            paused: false
+        }
+    }
+
+    pub fn queue_track(&mut self, track: Option<TRACKS>, transition: MusicTransition) {
+        // Queues a track change for the next step(), None fades to silence.
+        self.queued = Some((track, transition));
+    }
+
+    pub fn get_current_track(&self) -> Option<TRACKS> {
+        if let Some((track, _)) = self.waiting {
+            return Some(track);
+        }
+        return self.current.as_ref().map(|playing| playing.track);
+    }
+
+    pub fn handle_command(&mut self, mixer: &mut Mixer<'_>, command: MusicCommand) {
+        match command {
+            MusicCommand::PLAY(track, transition) => self.queue_track(Some(track), transition),
+            MusicCommand::STOP(transition) => self.queue_track(None, transition),
+            MusicCommand::PAUSE => {
+                // Paused notes are cut off, the track carries on from the same point when resumed.
+                if !self.paused {
+                    self.paused = true;
+                    self.stop_all(mixer);
+                }
+            },
+            MusicCommand::RESUME => self.paused = false,
+            MusicCommand::VOLUME(volume) => self.volume = volume.clamp(num!(0), num!(1)),
+            MusicCommand::TEMPO(tempo) => self.tempo = tempo,
+            MusicCommand::JUMP(pattern) => {
+                if let Some(current_track) = &mut self.current {
+                    current_track.jump_to_pattern(mixer, pattern);
+                }
+            },
+            MusicCommand::JINGLE(track) => self.start_jingle(mixer, track),
+            MusicCommand::RESET => {
+                self.stop_all(mixer);
+                *self = MusicPlayer {
+                    queued: self.queued.take(),
+                    ..MusicPlayer::new()
+                };
+            },
+        }
+    }
+
+    pub fn step(&mut self, mixer: &mut Mixer<'_>) {
+        // Should be called once per frame before mixer.frame().
+        if let Some((track, transition)) = self.queued.take() {
+            self.start_transition(mixer, track, transition);
+        }
+        if self.paused {
+            return;
+        }
+
+        self.tempo_counter += self.tempo;
+        let steps = self.tempo_counter.floor();
+        self.tempo_counter -= Num::new(steps);
+
+        if let Some(old_track) = &mut self.fading_out {
+            old_track.step(mixer, self.volume, steps);
+            if old_track.is_faded_out() {
+                old_track.tracker.stop(mixer);
+                self.fading_out = None;
+            }
+        }
+
+        if self.fading_out.is_none() && let Some((track, fade_in_frames)) = self.waiting.take() {
+            self.current = Some(PlayingTrack::new(track, fade_in_frames));
+        }
+
+        if let Some(current_track) = &mut self.current {
+            current_track.step(mixer, self.volume, steps);
+        }
+
+        if self.after_jingle.is_some() {
+            self.jingle_steps_left = self.jingle_steps_left.saturating_sub(steps);
+            if self.jingle_steps_left == 0 {
+                if let Some(mut jingle) = self.current.take() {
+                    jingle.tracker.stop(mixer);
+                }
+                self.current = self.after_jingle.take();
+            }
+        }
+    }
+
+    fn start_jingle(&mut self, mixer: &mut Mixer<'_>, track: TRACKS) {
+        let jingle_data = get_track_data(track);
+        if self.after_jingle.is_none() {
+            if let Some(current_track) = &mut self.current {
+                current_track.tracker.stop(mixer);
+            }
+            self.after_jingle = self.current.take();
+        } else if let Some(mut old_jingle) = self.current.take() {
+            old_jingle.tracker.stop(mixer);
+        }
+        self.current = Some(PlayingTrack::new(track, 0));
+        self.jingle_steps_left = get_steps_until_pattern(track, jingle_data.patterns_to_play.len());
+    }
+
+    fn stop_all(&mut self, mixer: &mut Mixer<'_>) {
+        for playing in [&mut self.current, &mut self.fading_out].into_iter().flatten() {
+            playing.tracker.stop(mixer);
+        }
+    }
+
+    fn start_transition(&mut self, mixer: &mut Mixer<'_>, track: Option<TRACKS>, transition: MusicTransition) {
+        // A track change during a jingle replaces the track it would have resumed.
+        self.after_jingle = None;
+        if self.get_current_track() == track {
+            return;
+        }
+        // Only one track fades out at a time, anything still fading is cut off.
+        if let Some(mut old_track) = self.fading_out.take() {
+            old_track.tracker.stop(mixer);
+        }
+        self.waiting = None;
+
+        let old_track = self.current.take();
+        match transition {
+            MusicTransition::CUT => {
+                if let Some(mut old_track) = old_track {
+                    old_track.tracker.stop(mixer);
+                }
+                self.current = track.map(|track| PlayingTrack::new(track, 0));
+            },
+            MusicTransition::CROSSFADE(frames) => {
+                self.fading_out = old_track.map(|mut old_track| { old_track.fade_out(frames); old_track });
+                self.current = track.map(|track| PlayingTrack::new(track, frames));
+            },
+            MusicTransition::FADE(frames) => {
+                self.fading_out = old_track.map(|mut old_track| { old_track.fade_out(frames); old_track });
+                self.waiting = track.map(|track| (track, frames));
+            },
+        }
+    }
+}
+
+/*
+    The tracker has no volume control of its own, so it is stepped through this wrapper
+    which scales the volume of every channel the tracker sets.
+    The tracker only ever holds one channel from channel() at a time, so a single
+    ScaledChannel is reused to point at whichever mixer channel it asked for.
+*/
+struct ScaledMixer<'a, 'gba> {
+    mixer: &'a mut Mixer<'gba>,
+    volume: Num<i16, 8>,
+    current_channel: ScaledChannel
+}
+
+impl<'a, 'gba> ScaledMixer<'a, 'gba> {
+    fn new(mixer: &'a mut Mixer<'gba>, volume: Num<i16, 8>) -> ScaledMixer<'a, 'gba> {
+        ScaledMixer {
+            mixer,
+            volume,
+            current_channel: ScaledChannel::Empty
+        }
+    }
+}
+
+impl agb_tracker::Mixer for ScaledMixer<'_, '_> {
+    type ChannelId = ChannelId;
+    type SoundChannel = ScaledChannel;
+
+    fn channel(&mut self, channel_id: &ChannelId) -> Option<&mut ScaledChannel> {
+        let volume = self.volume;
+        match self.mixer.channel(channel_id) {
+            Some(channel) => {
+                self.current_channel = ScaledChannel::Playing(channel as *mut SoundChannel, volume);
+                return Some(&mut self.current_channel);
+            },
+            None => {
+                self.current_channel = ScaledChannel::Empty;
+                return None;
+            },
+        }
+    }
+
+    fn play_sound(&mut self, channel: ScaledChannel) -> Option<ChannelId> {
+        match channel {
+            ScaledChannel::New(mut channel) => {
+                channel.volume(self.volume);
+                return self.mixer.play_sound(channel);
+            },
+            _ => return None,
+        }
+    }
+}
+
+// Steps a tracker without playing anything, used to fast forward it.
+struct NullMixer {}
+
+impl agb_tracker::Mixer for NullMixer {
+    type ChannelId = ChannelId;
+    type SoundChannel = ScaledChannel;
+
+    fn channel(&mut self, channel_id: &ChannelId) -> Option<&mut ScaledChannel> {
+        return None;
+    }
+
+    fn play_sound(&mut self, channel: ScaledChannel) -> Option<ChannelId> {
+        return None;
+    }
+}
+
+enum ScaledChannel {
+    Empty,
+    New(SoundChannel),
+    // Points into the mixer, only valid until the ScaledMixer is next used.
+    Playing(*mut SoundChannel, Num<i16, 8>)
+}
+
+impl ScaledChannel {
+    fn with_channel(&mut self, action: impl FnOnce(&mut SoundChannel, Num<i16, 8>)) -> &mut Self {
+        match self {
+            ScaledChannel::Empty => {},
+            ScaledChannel::New(channel) => action(channel, num!(1)),
+            // SAFETY: the pointer is only set by ScaledMixer::channel(), which returns this channel borrowed from
+            // the ScaledMixer. While that borrow is alive the ScaledMixer can not be used again, so its mixer is
+            // not touched and the SoundChannel can not move or be freed. Once the borrow ends the pointer is never
+            // read again, the next channel() call replaces it and play_sound() ignores Playing channels.
+            ScaledChannel::Playing(channel, scale) => action(unsafe { &mut **channel }, *scale),
+        }
+        return self;
+    }
+}
+
+impl agb_tracker::SoundChannel for ScaledChannel {
+    fn new(data: &Cow<'static, [u8]>) -> ScaledChannel {
+        return ScaledChannel::New(<SoundChannel as agb_tracker::SoundChannel>::new(data));
+    }
+
+    fn stop(&mut self) {
+        self.with_channel(|channel, _| channel.stop());
+    }
+
+    fn pause(&mut self) -> &mut Self {
+        return self.with_channel(|channel, _| { channel.pause(); });
+    }
+
+    fn resume(&mut self) -> &mut Self {
+        return self.with_channel(|channel, _| { channel.resume(); });
+    }
+
+    fn should_loop(&mut self) -> &mut Self {
+        return self.with_channel(|channel, _| { channel.should_loop(); });
+    }
+
+    fn volume(&mut self, value: impl Into<Num<i16, 8>>) -> &mut Self {
+        let value = value.into();
+        return self.with_channel(|channel, scale| { channel.volume(value * scale); });
+    }
+
+    fn restart_point(&mut self, value: impl Into<Num<u32, 8>>) -> &mut Self {
+        let value = value.into();
+        return self.with_channel(|channel, _| { channel.restart_point(value); });
+    }
+
+    fn playback(&mut self, playback_speed: impl Into<Num<u32, 8>>) -> &mut Self {
+        let playback_speed = playback_speed.into();
+        return self.with_channel(|channel, _| { channel.playback(playback_speed); });
+    }
+
+    fn panning(&mut self, panning: impl Into<Num<i16, 8>>) -> &mut Self {
+        let panning = panning.into();
+        return self.with_channel(|channel, _| { channel.panning(panning); });
+    }
+
+    fn set_pos(&mut self, pos: impl Into<Num<u32, 8>>) -> &mut Self {
+        let pos = pos.into();
+        return self.with_channel(|channel, _| { channel.set_pos(pos); });
+    }
+}
@@ -0,0 +1,218 @@
+/*
+    A* pathfinding over a grid laid over the scene, where any cell touched by a wall is blocked.
+    Objects ask for a path through GlobalData, then each frame the searches are stepped a
+    few cells at a time, so a long search is spread over several frames instead of stalling one.
+    Positions are in world space, which does not change as the camera scrolls. (see GlobalData::to_world_pos())
+    The grid covers a fixed 1024 by 512 pixel area around the starting screen, searches to or from outside it can not be started.
+    Each search slot keeps its buffers between searches, so asking for new paths does not allocate more than the path itself.
+*/
+
+use agb::fixnum::{Rect, Vector2D};
+use alloc::collections::BinaryHeap;
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+
+pub(crate) const CELL_SIZE: i32 = 16;
+const GRID_WIDTH: i32 = 64;
+const GRID_HEIGHT: i32 = 32;
+const GRID_ORIGIN: Vector2D<i32> = Vector2D { x: -256, y: -128 }; // Leaves room for the camera to scroll past the screen in every direction.
+const MAX_SEARCHES: usize = 4;
+const STEPS_PER_FRAME: usize = 64; // How many cells are checked each frame, shared between every search.
+const NO_CELL: u16 = u16::MAX;
+
+pub(crate) enum PathState {
+    SEARCHING,
+    FOUND(Vec<Vector2D<i32>>), // The top left of each cell along the path, from the starting cell to the goal.
+    FAILED // There is no path, or the search was dropped.
+}
+
+struct Search {
+    // One of the MAX_SEARCHES slots, reset and reused for each new search.
+    id: u16,
+    goal: u16,
+    open: BinaryHeap<Reverse<(u16, u16)>>, // The estimated total cost and cell of each cell waiting to be checked.
+    cost: Vec<u16>,
+    came_from: Vec<u16>,
+    state: PathState
+}
+
+pub(crate) struct Pathfinder {
+    blocked: Vec<bool>,
+    searches: Vec<Search>,
+    running: Vec<usize>, // The slots in use, in the order they were asked for.
+    next_id: u16
+}
+
+impl Pathfinder {
+    pub fn new() -> Pathfinder {
+        let cell_count = (GRID_WIDTH * GRID_HEIGHT) as usize;
+        let searches = (0..MAX_SEARCHES).map(|_| Search {
+            id: 0,
+            goal: 0,
+            open: BinaryHeap::new(),
+            cost: alloc::vec![u16::MAX; cell_count],
+            came_from: alloc::vec![NO_CELL; cell_count],
+            state: PathState::FAILED
+        }).collect();
+        Pathfinder {
+            blocked: alloc::vec![false; cell_count],
+            searches,
+            running: Vec::with_capacity(MAX_SEARCHES),
+            next_id: 0
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.clear_grid();
+        self.running.clear();
+    }
+
+    pub fn clear_grid(&mut self) {
+        self.blocked.fill(false);
+    }
+
+    pub fn block_rect(&mut self, col: Rect<i32>) {
+        // Blocks every cell the rect touches, anything outside the grid is ignored.
+        let top_left = get_cell_pos(col.position);
+        let bottom_right = get_cell_pos(col.position + col.size - Vector2D { x: 1, y: 1 });
+        for y in top_left.y.max(0)..=bottom_right.y.min(GRID_HEIGHT - 1) {
+            for x in top_left.x.max(0)..=bottom_right.x.min(GRID_WIDTH - 1) {
+                self.blocked[(y * GRID_WIDTH + x) as usize] = true;
+            }
+        }
+    }
+
+    pub fn request(&mut self, from: Vector2D<i32>, to: Vector2D<i32>) -> Option<u16> {
+        // Starts a search between the cells holding the two points, returns its id or None if it can not be started.
+        // Makes room by dropping the oldest finished search nobody has taken yet.
+        let (Some(start), Some(goal)) = (get_cell(from), get_cell(to)) else {
+            return None;
+        };
+        if self.running.len() >= MAX_SEARCHES {
+            let finished = self.running.iter().position(|slot| !matches!(self.searches[*slot].state, PathState::SEARCHING))?;
+            self.running.remove(finished);
+        }
+        let slot = (0..MAX_SEARCHES).find(|slot| !self.running.contains(slot))?;
+        let id = self.next_id;
+        let search = &mut self.searches[slot];
+        search.id = id;
+        search.goal = goal;
+        search.open.clear();
+        search.cost.fill(u16::MAX);
+        search.came_from.fill(NO_CELL);
+        search.state = PathState::SEARCHING;
+        search.cost[start as usize] = 0;
+        search.open.push(Reverse((get_estimate(start, goal), start)));
+        self.running.push(slot);
+        self.next_id = self.next_id.wrapping_add(1);
+        return Some(id);
+    }
+
+    pub fn step(&mut self) {
+        // Works through the searches in the order they were asked for, until this frames steps are used up.
+        let mut steps_left = STEPS_PER_FRAME;
+        for slot in self.running.iter() {
+            if steps_left == 0 {
+                break;
+            }
+            let search = &mut self.searches[*slot];
+            if matches!(search.state, PathState::SEARCHING) {
+                steps_left -= step_search(search, &self.blocked, steps_left);
+            }
+        }
+    }
+
+    pub fn take(&mut self, id: u16) -> PathState {
+        // Hands over a finished search and forgets it, searches still running are left alone.
+        let Some(index) = self.running.iter().position(|slot| self.searches[*slot].id == id) else {
+            return PathState::FAILED;
+        };
+        let slot = self.running[index];
+        if matches!(self.searches[slot].state, PathState::SEARCHING) {
+            return PathState::SEARCHING;
+        }
+        self.running.remove(index);
+        return core::mem::replace(&mut self.searches[slot].state, PathState::FAILED);
+    }
+}
+
+fn step_search(search: &mut Search, blocked: &[bool], steps: usize) -> usize {
+    // Checks up to the given number of cells, returns how many were used.
+    let mut used = 0;
+    while used < steps {
+        let Some(Reverse((estimate, cell))) = search.open.pop() else {
+            search.state = PathState::FAILED;
+            break;
+        };
+        used += 1;
+        if cell == search.goal {
+            search.state = PathState::FOUND(build_path(search));
+            break;
+        }
+        let cost = search.cost[cell as usize];
+        if estimate > cost + get_estimate(cell, search.goal) {
+            // A cheaper way to this cell was already found after this entry was added.
+            continue;
+        }
+        for next in get_neighbours(cell).into_iter().flatten() {
+            // The goal is allowed even when blocked, as whatever is being chased may be standing next to a wall.
+            if blocked[next as usize] && next != search.goal {
+                continue;
+            }
+            let next_cost = cost + 1;
+            if next_cost < search.cost[next as usize] {
+                search.cost[next as usize] = next_cost;
+                search.came_from[next as usize] = cell;
+                search.open.push(Reverse((next_cost + get_estimate(next, search.goal), next)));
+            }
+        }
+    }
+    return used;
+}
+
+fn build_path(search: &Search) -> Vec<Vector2D<i32>> {
+    let mut path: Vec<Vector2D<i32>> = Vec::new();
+    let mut cell = search.goal;
+    while cell != NO_CELL {
+        path.push(get_cell_top_left(cell));
+        cell = search.came_from[cell as usize];
+    }
+    path.reverse();
+    return path;
+}
+
+fn get_cell_pos(pos: Vector2D<i32>) -> Vector2D<i32> {
+    let offset = pos - GRID_ORIGIN;
+    return Vector2D { x: offset.x.div_euclid(CELL_SIZE), y: offset.y.div_euclid(CELL_SIZE) };
+}
+
+fn get_cell(pos: Vector2D<i32>) -> Option<u16> {
+    let cell = get_cell_pos(pos);
+    if cell.x < 0 || cell.y < 0 || cell.x >= GRID_WIDTH || cell.y >= GRID_HEIGHT {
+        return None;
+    }
+    return Some((cell.y * GRID_WIDTH + cell.x) as u16);
+}
+
+fn get_cell_top_left(cell: u16) -> Vector2D<i32> {
+    let cell = cell as i32;
+    return GRID_ORIGIN + Vector2D { x: cell % GRID_WIDTH, y: cell / GRID_WIDTH } * CELL_SIZE;
+}
+
+fn get_estimate(cell: u16, goal: u16) -> u16 {
+    // The number of cells between them moving only up, down, left and right.
+    let (cell, goal) = (cell as i32, goal as i32);
+    let distance = (cell % GRID_WIDTH - goal % GRID_WIDTH).abs() + (cell / GRID_WIDTH - goal / GRID_WIDTH).abs();
+    return distance as u16;
+}
+
+fn get_neighbours(cell: u16) -> [Option<u16>; 4] {
+    let (x, y) = (cell as i32 % GRID_WIDTH, cell as i32 / GRID_WIDTH);
+    let to_cell = |x: i32, y: i32| {
+        if x < 0 || y < 0 || x >= GRID_WIDTH || y >= GRID_HEIGHT {
+            return None;
+        }
+        return Some((y * GRID_WIDTH + x) as u16);
+    };
+    return [to_cell(x, y - 1), to_cell(x, y + 1), to_cell(x - 1, y), to_cell(x + 1, y)];
+}
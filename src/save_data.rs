@@ -0,0 +1,166 @@
+#![allow(dead_code, unused_variables)]
+
+/*
+    The save file, stored on the cartridges SRAM.
+    The file starts with a header, then each system that saves data writes its own section
+    with a id and length, so sections can grow or be added without breaking older saves.
+    Readers return None once they run out of data, so missing values keep their defaults.
+*/
+
+use agb::save::SaveData;
+use alloc::vec::Vec;
+
+const SAVE_MAGIC: [u8; 4] = *b"FMNT";
+const SAVE_VERSION: u8 = 1;
+const HEADER_SIZE: usize = 8;
+const SECTION_HEADER_SIZE: usize = 3;
+const MAX_SAVE_SIZE: usize = 4096;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SaveSection {
+    InputBindings,
+    Inventory
+}
+
+pub(crate) struct SaveWriter {
+    data: Vec<u8>,
+    section_start: Option<usize>
+}
+
+impl SaveWriter {
+    pub fn new() -> SaveWriter {
+        SaveWriter {
+            data: Vec::new(),
+            section_start: None
+        }
+    }
+
+    pub fn begin_section(&mut self, section: SaveSection) {
+        self.end_section();
+        self.data.push(section as u8);
+        self.section_start = Some(self.data.len());
+        self.write_u16(0); // Filled in by end_section()
+    }
+
+    pub fn end_section(&mut self) {
+        if let Some(start) = self.section_start.take() {
+            let length = (self.data.len() - start - 2) as u16;
+            self.data[start..start + 2].copy_from_slice(&length.to_le_bytes());
+        }
+    }
+
+    pub fn write_u8(&mut self, value: u8) {
+        self.data.push(value);
+    }
+
+    pub fn write_u16(&mut self, value: u16) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_u32(&mut self, value: u32) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn into_bytes(mut self) -> Vec<u8> {
+        self.end_section();
+        return self.data;
+    }
+}
+
+pub(crate) struct SaveReader<'a> {
+    data: &'a [u8],
+    pos: usize
+}
+
+impl SaveReader<'_> {
+    pub fn read_u8(&mut self) -> Option<u8> {
+        let value = *self.data.get(self.pos)?;
+        self.pos += 1;
+        return Some(value);
+    }
+
+    pub fn read_u16(&mut self) -> Option<u16> {
+        let bytes = self.data.get(self.pos..self.pos + 2)?;
+        self.pos += 2;
+        return Some(u16::from_le_bytes([bytes[0], bytes[1]]));
+    }
+
+    pub fn read_u32(&mut self) -> Option<u32> {
+        let bytes = self.data.get(self.pos..self.pos + 4)?;
+        self.pos += 4;
+        return Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]));
+    }
+}
+
+pub(crate) struct SaveFile {
+    media: Option<SaveData>,
+    sections: Vec<u8>
+}
+
+impl SaveFile {
+    pub fn new() -> SaveFile {
+        SaveFile {
+            media: None,
+            sections: Vec::new()
+        }
+    }
+
+    pub fn set_media(&mut self, media: Option<SaveData>) {
+        self.media = media;
+    }
+
+    pub fn get_section(&self, section: SaveSection) -> Option<SaveReader<'_>> {
+        // Finds a section in the last file loaded or written.
+        let mut pos = 0;
+        while pos + SECTION_HEADER_SIZE <= self.sections.len() {
+            let id = self.sections[pos];
+            let length = u16::from_le_bytes([self.sections[pos + 1], self.sections[pos + 2]]) as usize;
+            let start = pos + SECTION_HEADER_SIZE;
+            let end = (start + length).min(self.sections.len());
+            if id == section as u8 {
+                return Some(SaveReader { data: &self.sections[start..end], pos: 0 });
+            }
+            pos = end;
+        }
+        return None;
+    }
+
+    pub fn load(&mut self) -> Result<(), &'static str> {
+        let media = match &mut self.media {
+            Some(media) => media,
+            None => return Err("No save media to load from!"),
+        };
+        let mut header = [0u8; HEADER_SIZE];
+        media.read(0, &mut header).map_err(|_| "Unable to read the save header!")?;
+        if header[0..4] != SAVE_MAGIC || header[4] != SAVE_VERSION {
+            return Err("No save file found, using defaults");
+        }
+        let length = (u16::from_le_bytes([header[6], header[7]]) as usize).min(MAX_SAVE_SIZE - HEADER_SIZE);
+        let mut sections = alloc::vec![0u8; length];
+        media.read(HEADER_SIZE, &mut sections).map_err(|_| "Unable to read the save file!")?;
+        self.sections = sections;
+        return Ok(());
+    }
+
+    pub fn write(&mut self, writer: SaveWriter) -> Result<(), &'static str> {
+        let sections = writer.into_bytes();
+        if HEADER_SIZE + sections.len() > MAX_SAVE_SIZE {
+            return Err("Save file is too large!");
+        }
+        let media = match &mut self.media {
+            Some(media) => media,
+            None => return Err("No save media to write to!"),
+        };
+        let mut file = Vec::with_capacity(HEADER_SIZE + sections.len());
+        file.extend_from_slice(&SAVE_MAGIC);
+        file.push(SAVE_VERSION);
+        file.push(0);
+        file.extend_from_slice(&(sections.len() as u16).to_le_bytes());
+        file.extend_from_slice(&sections);
+
+        let mut block = media.prepare_write(0..file.len()).map_err(|_| "Unable to prepare the save media!")?;
+        block.write(0, &file).map_err(|_| "Unable to write the save file!")?;
+        self.sections = sections;
+        return Ok(());
+    }
+}
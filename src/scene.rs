@@ -6,18 +6,44 @@
 use agb::fixnum::Vector2D;
 use alloc::vec::Vec;
 use crate::actor::Actor;
+use crate::actor::enemy::Behaviour;
+use crate::actor::gate::GateType;
+use crate::actor::pickup::PickupType;
+use crate::actor::platform::PlatformPath;
+use crate::actor::terrain::TerrainType;
+use crate::actor::trigger::TriggerType;
+use crate::collision::Slope;
+use crate::global_data::Flags;
+use crate::inventory::Items;
+use crate::music::MusicTransition;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq)]
 pub enum SCENES {
     TestScene,
     Map001
 }
 
+impl SCENES {
+    pub fn from_index(index: usize) -> Option<SCENES> {
+        // Used when loading a scene saved as a number, each scene needs a match case here.
+        match index {
+            0 => return Some(SCENES::TestScene),
+            1 => return Some(SCENES::Map001),
+            _ => return None,
+        }
+    }
+}
+
 pub enum BACKGROUNDS {
     BgImg1,
     BgImg2
 }
 
+#[derive(Clone, Copy, PartialEq)]
+pub enum TRACKS {
+    Bgm1
+}
+
 pub fn get_layout(scene: SCENES) -> Vec<Actor> {
     let mut new_obj_box: Vec<Actor> = Vec::new();
     match scene {
@@ -29,6 +55,27 @@ pub fn get_layout(scene: SCENES) -> Vec<Actor> {
             new_obj_box.push(Actor::AWall(Vector2D { x: -120, y: 50 }));
             new_obj_box.push(Actor::AWall(Vector2D { x: 346, y: 50 }));
             new_obj_box.push(Actor::AWall(Vector2D { x: 150, y: -20 }));
+            new_obj_box.push(Actor::AEnemy(Vector2D { x: 150, y: 100 }, Behaviour::PATROL(Vector2D { x: 40, y: 0 })));
+            new_obj_box.push(Actor::AEnemy(Vector2D { x: -80, y: -50 }, Behaviour::IDLE));
+            new_obj_box.push(Actor::AEnemy(Vector2D { x: -40, y: -50 }, Behaviour::CHASE(40)));
+            new_obj_box.push(Actor::AEnemy(Vector2D { x: 0, y: -50 }, Behaviour::FLEE(40)));
+            new_obj_box.push(Actor::AEnemy(Vector2D { x: -80, y: 130 }, Behaviour::HUNT(48)));
+            new_obj_box.push(Actor::AEnemy(Vector2D { x: 300, y: -50 }, Behaviour::SHOOT(64, 90)));
+            new_obj_box.push(Actor::AEnemy(Vector2D { x: 300, y: 20 }, Behaviour::SNIPE(64, 120)));
+            new_obj_box.push(Actor::APickup(Vector2D { x: 100, y: 20 }, PickupType::ITEM(Items::Coin, 1)));
+            new_obj_box.push(Actor::APickup(Vector2D { x: 130, y: 20 }, PickupType::HEALTH(1)));
+            new_obj_box.push(Actor::ATrigger(Vector2D { x: 100, y: 130 }, TriggerType::LEVER, Flags::TestWallSwitch));
+            new_obj_box.push(Actor::AGate(Vector2D { x: 200, y: 80 }, GateType::DOOR, Flags::TestWallSwitch));
+            new_obj_box.push(Actor::ATrigger(Vector2D { x: 150, y: 130 }, TriggerType::PLATE, Flags::TestBridgeSwitch));
+            new_obj_box.push(Actor::AGate(Vector2D { x: 180, y: 130 }, GateType::BRIDGE, Flags::TestBridgeSwitch));
+            new_obj_box.push(Actor::ATrigger(Vector2D { x: 280, y: 60 }, TriggerType::AREA(Vector2D { x: 32, y: 32 }), Flags::TestTrapArea));
+            new_obj_box.push(Actor::AGate(Vector2D { x: 264, y: 60 }, GateType::BARRIER, Flags::TestTrapArea));
+            new_obj_box.push(Actor::APlatform(Vector2D { x: 20, y: 100 }, PlatformPath::PINGPONG(Vector2D { x: 60, y: 0 }, 120), 2));
+            new_obj_box.push(Actor::APlatform(Vector2D { x: 180, y: -60 }, PlatformPath::LINEAR(Vector2D { x: 40, y: 0 }, 90), 2));
+            new_obj_box.push(Actor::APlatform(Vector2D { x: 200, y: 20 }, PlatformPath::CIRCULAR(24, 180), 1));
+            new_obj_box.push(Actor::ATerrain(Vector2D { x: 240, y: 120 }, TerrainType::RAMP(Slope::RISING)));
+            new_obj_box.push(Actor::ATerrain(Vector2D { x: 256, y: 104 }, TerrainType::LEDGE(2)));
+            new_obj_box.push(Actor::ATerrain(Vector2D { x: 300, y: 130 }, TerrainType::LONGRAMP(Slope::FALLING)));
         },
         SCENES::Map001 => {
             new_obj_box.push(Actor::APlayer(Vector2D { x: 50, y: 50 }));
@@ -48,4 +95,28 @@ pub fn get_bg_val(scene: SCENES) -> Option<BACKGROUNDS> {
         }
     };
 
+}
+
+pub fn get_track(scene: SCENES) -> Option<TRACKS> {
+    // The BGM for the scene, None for silence. Scenes sharing a track keep it playing through the scene change.
+    match scene {
+        SCENES::TestScene => {
+            return Some(TRACKS::Bgm1);
+        },
+        SCENES::Map001 => {
+            return Some(TRACKS::Bgm1);
+        }
+    };
+}
+
+pub fn get_music_transition(scene: SCENES) -> MusicTransition {
+    // How the previous scenes BGM changes over to this scenes BGM.
+    match scene {
+        SCENES::TestScene => {
+            return MusicTransition::CUT;
+        },
+        SCENES::Map001 => {
+            return MusicTransition::CROSSFADE(60);
+        }
+    };
 }
\ No newline at end of file
@@ -0,0 +1,112 @@
+#![allow(dead_code, unused_variables)]
+
+/*
+    The sound effect manager, contains a enum list of all sound effects in the game.
+    Objects queue sound effects through GlobalData, then the queue is played
+    each frame on the same mixer as the BGM tracker.
+    Sound effects are limited to MAX_SFX_CHANNELS at once so the BGM keeps its channels,
+    when full a new effect replaces the lowest priority one playing if it has a higher priority.
+*/
+
+use agb::fixnum::{Num, num};
+use agb::include_wav;
+use agb::sound::mixer::{ChannelId, Mixer, SoundChannel, SoundData};
+use alloc::vec::Vec;
+
+// The mixer has 8 channels in total, shared with the BGM.
+const MAX_SFX_CHANNELS: usize = 3;
+
+static BLIP: SoundData = include_wav!("sfx/blip.wav");
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SFX {
+    Blip
+}
+
+fn get_sound_data(sound: SFX) -> SoundData {
+    match sound {
+        SFX::Blip => return BLIP,
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct SfxRequest {
+    sound: SFX,
+    priority: u8,
+    volume: Num<i16, 8>,
+    panning: Num<i16, 8>
+}
+
+impl SfxRequest {
+    // Higher priority effects can cut off lower priority ones, volume is 0 to 1 and panning is -1 (left) to 1 (right).
+    pub fn new(sound: SFX, priority: u8, volume: Num<i16, 8>, panning: Num<i16, 8>) -> SfxRequest {
+        SfxRequest {
+            sound,
+            priority,
+            volume,
+            panning
+        }
+    }
+
+    pub fn from_sound(sound: SFX) -> SfxRequest {
+        return SfxRequest::new(sound, 0, num!(1), num!(0));
+    }
+}
+
+struct PlayingSfx {
+    channel: ChannelId,
+    priority: u8
+}
+
+pub(crate) struct SfxPlayer {
+    playing: Vec<PlayingSfx>
+}
+
+impl SfxPlayer {
+    pub fn new() -> SfxPlayer {
+        SfxPlayer {
+            playing: Vec::new()
+        }
+    }
+
+    pub fn process_queue(&mut self, mixer: &mut Mixer<'_>, queue: Vec<SfxRequest>) {
+        // Forgets any effects that have finished, then plays everything queued this frame.
+        self.playing.retain(|sfx| mixer.channel(&sfx.channel).is_some());
+        for request in queue {
+            self.play(mixer, request);
+        }
+    }
+
+    pub fn stop_all(&mut self, mixer: &mut Mixer<'_>) {
+        for sfx in self.playing.drain(..) {
+            if let Some(channel) = mixer.channel(&sfx.channel) {
+                channel.stop();
+            }
+        }
+    }
+
+    fn play(&mut self, mixer: &mut Mixer<'_>, request: SfxRequest) {
+        if self.playing.len() >= MAX_SFX_CHANNELS {
+            // Finds the lowest priority effect, oldest first, and stops it if the new one is more important.
+            let mut lowest_index = 0;
+            for (index, sfx) in self.playing.iter().enumerate() {
+                if sfx.priority < self.playing[lowest_index].priority {
+                    lowest_index = index;
+                }
+            }
+            if self.playing[lowest_index].priority >= request.priority {
+                return;
+            }
+            let replaced = self.playing.remove(lowest_index);
+            if let Some(channel) = mixer.channel(&replaced.channel) {
+                channel.stop();
+            }
+        }
+
+        let mut channel = SoundChannel::new(get_sound_data(request.sound));
+        channel.volume(request.volume).panning(request.panning);
+        if let Some(channel_id) = mixer.play_sound(channel) {
+            self.playing.push(PlayingSfx { channel: channel_id, priority: request.priority });
+        }
+    }
+}
@@ -0,0 +1,153 @@
+/*
+    A copy of every objects collider taken at the end of each frame, so objects can
+    ask about the world around them from update() without access to the obj_box.
+    Objects use this through GlobalData, which moves the colliders by the current
+    camera scroll so they line up with positions in this frame.
+    Background tiles are not checked, so only object colliders block casts.
+    Walls are also drawn onto the pathfinding grid here, so searches see the same world.
+*/
+
+use agb::fixnum::{Rect, Vector2D};
+use alloc::{boxed::Box, vec::Vec};
+use crate::game_obj::{GameObj, ResponseType};
+use crate::pathfinding::{PathState, Pathfinder};
+
+#[derive(Clone, Copy)]
+pub(crate) struct RayHit {
+    pub point: Vector2D<i32>, // Where the cast first touched the collider.
+    pub response: ResponseType // What the object that was hit responds as.
+}
+
+#[derive(Clone, Copy)]
+struct WorldCollider {
+    col: Rect<i32>,
+    response: ResponseType
+}
+
+// A point along a cast as a fraction, so it can be compared without rounding.
+#[derive(Clone, Copy)]
+struct Fraction {
+    num: i32,
+    den: i32 // Always above 0.
+}
+
+impl Fraction {
+    fn less_than(&self, other: Fraction) -> bool {
+        return (self.num as i64) * (other.den as i64) < (other.num as i64) * (self.den as i64);
+    }
+}
+
+pub(crate) struct WorldQuery {
+    colliders: Vec<WorldCollider>,
+    pathfinder: Pathfinder
+}
+
+impl WorldQuery {
+    pub fn new() -> WorldQuery {
+        WorldQuery {
+            colliders: Vec::new(),
+            pathfinder: Pathfinder::new()
+        }
+    }
+
+    pub fn rebuild(&mut self, obj_box: &[Box<dyn GameObj>], world_offset: Vector2D<i32>) {
+        // Sensors are left out, as they never block anything.
+        // The pathfinding grid is in world space, so walls are moved by how far the camera has scrolled.
+        self.colliders.clear();
+        self.pathfinder.clear_grid();
+        for obj in obj_box {
+            if obj.is_sensor() {
+                continue;
+            }
+            if let Some(col) = obj.get_collider() {
+                let response = obj.check_response_type();
+                if response == ResponseType::WALL {
+                    self.pathfinder.block_rect(Rect { position: col.position + world_offset, size: col.size });
+                }
+                self.colliders.push(WorldCollider { col, response });
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.colliders.clear();
+        self.pathfinder.clear();
+    }
+
+    pub fn find_path(&mut self, from: Vector2D<i32>, to: Vector2D<i32>) -> Option<u16> {
+        return self.pathfinder.request(from, to);
+    }
+
+    pub fn step_paths(&mut self) {
+        self.pathfinder.step();
+    }
+
+    pub fn take_path(&mut self, id: u16) -> PathState {
+        return self.pathfinder.take(id);
+    }
+
+    pub fn cast_segment(&self, from: Vector2D<i32>, to: Vector2D<i32>, hits: &[ResponseType], offset: Vector2D<i32>) -> Option<RayHit> {
+        // Finds the first collider using one of the hit ResponseTypes along the line, using each colliders bounds.
+        // Colliders the line starts inside are skipped, so objects can cast from their own center.
+        let mut closest: Option<(Fraction, ResponseType)> = None;
+        for collider in self.colliders.iter() {
+            if !hits.contains(&collider.response) {
+                continue;
+            }
+            let col = Rect { position: collider.col.position - offset, size: collider.col.size };
+            let Some(enter) = get_enter_point(from, to, col) else {
+                continue;
+            };
+            let nearer = match closest {
+                Some((closest_enter, _)) => enter.less_than(closest_enter),
+                None => true,
+            };
+            if nearer {
+                closest = Some((enter, collider.response));
+            }
+        }
+        let (enter, response) = closest?;
+        let motion = to - from;
+        let point = Vector2D {
+            x: from.x + motion.x * enter.num / enter.den,
+            y: from.y + motion.y * enter.num / enter.den
+        };
+        return Some(RayHit { point, response });
+    }
+}
+
+fn get_enter_point(from: Vector2D<i32>, to: Vector2D<i32>, col: Rect<i32>) -> Option<Fraction> {
+    // Where along the line it enters the collider, from 0 at the start to 1 at the end, checking each axis in turn.
+    let mut enter = Fraction { num: 0, den: 1 };
+    let mut exit = Fraction { num: 1, den: 1 };
+    let mut started_outside = false;
+    for (start, distance, min, max) in [
+        (from.x, to.x - from.x, col.position.x, col.position.x + col.size.x),
+        (from.y, to.y - from.y, col.position.y, col.position.y + col.size.y)
+    ] {
+        if distance == 0 {
+            if start < min || start >= max {
+                return None;
+            }
+            continue;
+        }
+        let (near, far) = if distance > 0 {
+            (Fraction { num: min - start, den: distance }, Fraction { num: max - start, den: distance })
+        } else {
+            (Fraction { num: start - max, den: -distance }, Fraction { num: start - min, den: -distance })
+        };
+        if near.num > 0 {
+            started_outside = true;
+        }
+        if enter.less_than(near) {
+            enter = near;
+        }
+        if far.less_than(exit) {
+            exit = far;
+        }
+    }
+    if !started_outside || !enter.less_than(exit) {
+        return None;
+    }
+    return Some(enter);
+}